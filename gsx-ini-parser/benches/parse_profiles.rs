@@ -0,0 +1,60 @@
+use std::{fs, path::PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use gsx_ini_parser::{from_section, General, Tokenizer};
+
+const PROFILE_COUNT: usize = 500;
+
+fn get_test_file_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("res/test/lszh-fsdt.ini");
+    path
+}
+
+/// A folder with the LSZH test profile copied under 500 different names.
+fn create_profile_folder() -> PathBuf {
+    let folder = std::env::temp_dir().join("gsx-ini-parser-bench");
+    fs::create_dir_all(&folder).unwrap();
+    let text = fs::read(get_test_file_path()).unwrap();
+    for index in 0..PROFILE_COUNT {
+        fs::write(folder.join(format!("X{:03}-bench.ini", index)), &text).unwrap();
+    }
+    folder
+}
+
+fn parse_single_profile(c: &mut Criterion) {
+    let text = fs::read_to_string(get_test_file_path()).unwrap();
+
+    c.bench_function("tokenize lszh-fsdt.ini", |b| {
+        b.iter(|| Tokenizer::new(&text).count())
+    });
+    c.bench_function("parse_document_str lszh-fsdt.ini", |b| {
+        b.iter(|| gsx_ini_parser::parse_document_str(&text))
+    });
+}
+
+/// What GSX Manager does on startup: parse every profile in the folder and read its creator.
+fn parse_profile_folder(c: &mut Criterion) {
+    let folder = create_profile_folder();
+
+    let mut group = c.benchmark_group("startup");
+    group.sample_size(10);
+    group.bench_function("parse 500 profiles", |b| {
+        b.iter(|| {
+            let mut creators = vec![];
+            for entry in fs::read_dir(&folder).unwrap() {
+                let parsed = gsx_ini_parser::parse_document(entry.unwrap().path()).unwrap();
+                let general: General = from_section(&parsed.document, "general").unwrap();
+                creators.push(general.creator);
+            }
+            assert_eq!(creators.len(), PROFILE_COUNT);
+            creators
+        })
+    });
+    group.finish();
+
+    fs::remove_dir_all(folder).unwrap();
+}
+
+criterion_group!(benches, parse_single_profile, parse_profile_folder);
+criterion_main!(benches);
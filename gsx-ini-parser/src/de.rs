@@ -0,0 +1,470 @@
+//! Serde support. A document deserializes as a map of section name to section, and a section as a
+//! map of key to value. Values are strings that are parsed into the type the target field asks
+//! for: numbers, `0`/`1` booleans, space separated sequences (`loadertype`), [`GeoPosition`] and
+//! [`ObjectPosition`]. An empty value deserializes as `None` for optional fields.
+//!
+//! A struct field called `extra` receives every key of the section that doesn't match another
+//! field, so unknown keys are kept instead of dropped. `#[serde(flatten)]` can't be used for this,
+//! because it hands values to the fields as plain strings.
+
+use std::{error::Error, fmt};
+
+use serde::{
+    de::{self, value::SeqDeserializer, DeserializeSeed, IntoDeserializer, MapAccess, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use crate::{
+    parse_airline_codes, parse_bool, parse_deice_areas, parse_labels, parse_object_position,
+    parse_position, parse_pushback_add_positions, parse_tuple_list, DeiceArea, GeoPosition, IniDocument,
+    ObjectPosition, PushbackAddPosition, TupleValue,
+};
+
+const EXTRA_FIELD: &str = "extra";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeError {
+    pub section: Option<String>,
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Self {
+            section: None,
+            key: None,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.section, &self.key) {
+            (Some(section), Some(key)) => write!(f, "[{}] {}: {}", section, key, self.message),
+            (Some(section), None) => write!(f, "[{}]: {}", section, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for DeError {}
+
+impl DeError {
+    fn in_section(mut self, section: &str) -> Self {
+        self.section.get_or_insert_with(|| String::from(section));
+        self
+    }
+
+    fn at_key(mut self, key: &str) -> Self {
+        self.key.get_or_insert_with(|| String::from(key));
+        self
+    }
+}
+
+/// Deserializes the whole document. Sections that appear more than once are merged.
+pub fn from_document<'a, T: Deserialize<'a>>(document: &'a IniDocument) -> Result<T, DeError> {
+    T::deserialize(DocumentDeserializer { document })
+}
+
+/// Deserializes a single section, merged with any other section of the same name.
+pub fn from_section<'a, T: Deserialize<'a>>(
+    document: &'a IniDocument,
+    section_name: &'a str,
+) -> Result<T, DeError> {
+    if document.section(section_name).is_none() {
+        return Err(de::Error::custom(format!(
+            "section [{}] not found",
+            section_name
+        )));
+    }
+    T::deserialize(section_deserializer(document, section_name))
+}
+
+/// For `#[serde(deserialize_with = "...")]` on `|` separated label lists like `pushbacklabels`.
+pub fn labels<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(parse_labels(&String::deserialize(deserializer)?))
+}
+
+/// For `#[serde(deserialize_with = "...")]` on `airlinecodes`.
+pub fn airline_codes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(parse_airline_codes(&String::deserialize(deserializer)?))
+}
+
+/// For `#[serde(deserialize_with = "...")]` on tuple lists like `deice_areas`.
+pub fn tuple_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Vec<TupleValue>>, D::Error> {
+    parse_tuple_list(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+/// For `#[serde(deserialize_with = "...")]` on `deice_areas`.
+pub fn deice_areas<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<DeiceArea>, D::Error> {
+    parse_deice_areas(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+/// For `#[serde(deserialize_with = "...")]` on `pushbackaddpos`.
+pub fn pushback_add_positions<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<PushbackAddPosition>, D::Error> {
+    parse_pushback_add_positions(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+impl<'de> Deserialize<'de> for GeoPosition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        parse_position(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectPosition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        parse_object_position(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+fn section_deserializer<'a>(
+    document: &'a IniDocument,
+    section_name: &'a str,
+) -> SectionDeserializer<'a> {
+    SectionDeserializer {
+        name: section_name,
+        entries: document.merged_entries(section_name),
+    }
+}
+
+struct DocumentDeserializer<'a> {
+    document: &'a IniDocument,
+}
+
+impl<'de> Deserializer<'de> for DocumentDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_map(SectionsAccess {
+            document: self.document,
+            section_names: self.document.section_names().into_iter(),
+            current_section: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct SectionsAccess<'a, I: Iterator<Item = &'a str>> {
+    document: &'a IniDocument,
+    section_names: I,
+    current_section: Option<&'a str>,
+}
+
+impl<'de, I: Iterator<Item = &'de str>> MapAccess<'de> for SectionsAccess<'de, I> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        self.current_section = self.section_names.next();
+        match self.current_section {
+            Some(section_name) => seed.deserialize(section_name.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let section_name = self
+            .current_section
+            .ok_or_else(|| <DeError as de::Error>::custom("value requested before key"))?;
+        seed.deserialize(section_deserializer(self.document, section_name))
+            .map_err(|error| error.in_section(section_name))
+    }
+}
+
+struct SectionDeserializer<'a> {
+    name: &'a str,
+    entries: Vec<(&'a str, &'a str)>,
+}
+
+impl<'de> Deserializer<'de> for SectionDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor
+            .visit_map(EntriesAccess::new(self.entries, None))
+            .map_err(|error| error.in_section(self.name))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        if !fields.contains(&EXTRA_FIELD) {
+            return self.deserialize_any(visitor);
+        }
+        let (known_entries, extra_entries) = self
+            .entries
+            .into_iter()
+            .partition(|(key, _)| *key != EXTRA_FIELD && fields.contains(key));
+        visitor
+            .visit_map(EntriesAccess::new(known_entries, Some(extra_entries)))
+            .map_err(|error| error.in_section(self.name))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct EntriesAccess<'a> {
+    entries: std::vec::IntoIter<(&'a str, &'a str)>,
+    /// Keys without a field of their own, handed to the `extra` field after all other keys.
+    extra_entries: Option<Vec<(&'a str, &'a str)>>,
+    current_entry: Option<(&'a str, &'a str)>,
+    extra_pending: bool,
+}
+
+impl<'a> EntriesAccess<'a> {
+    fn new(
+        entries: Vec<(&'a str, &'a str)>,
+        extra_entries: Option<Vec<(&'a str, &'a str)>>,
+    ) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            extra_entries,
+            current_entry: None,
+            extra_pending: false,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for EntriesAccess<'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        self.current_entry = self.entries.next();
+        if let Some((key, _)) = self.current_entry {
+            return seed.deserialize(key.into_deserializer()).map(Some);
+        }
+        if self.extra_entries.is_some() && !self.extra_pending {
+            self.extra_pending = true;
+            return seed.deserialize(EXTRA_FIELD.into_deserializer()).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        if let Some((key, value)) = self.current_entry {
+            return seed
+                .deserialize(ValueDeserializer { value })
+                .map_err(|error| error.at_key(key));
+        }
+        match self.extra_entries.take() {
+            Some(extra_entries) => seed.deserialize(SectionDeserializer {
+                name: "",
+                entries: extra_entries,
+            }),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+}
+
+struct ValueDeserializer<'a> {
+    value: &'a str,
+}
+
+impl ValueDeserializer<'_> {
+    fn parse<T: std::str::FromStr>(&self, expected: &str) -> Result<T, DeError> {
+        self.value
+            .trim()
+            .parse::<T>()
+            .map_err(|_| de::Error::custom(format!("'{}' is not {}", self.value, expected)))
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_bool(parse_bool(self.value).map_err(de::Error::custom)?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i8(self.parse("an integer")?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i16(self.parse("an integer")?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i32(self.parse("an integer")?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i64(self.parse("an integer")?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u8(self.parse("an unsigned integer")?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u16(self.parse("an unsigned integer")?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u32(self.parse("an unsigned integer")?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u64(self.parse("an unsigned integer")?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_f32(self.parse("a number")?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_f64(self.parse("a number")?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.value.trim().is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let items = self
+            .value
+            .split_whitespace()
+            .map(|value| ValueDeserializer { value });
+        visitor.visit_seq(SeqDeserializer::new(items))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_enum(self.value.trim().into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parse_document_str;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStand {
+        #[serde(rename = "type")]
+        parking_type: Option<u32>,
+        maxwingspan: Option<f64>,
+        hasjetway: bool,
+        loadertype: Vec<i64>,
+        airlinecodes: Option<String>,
+        pushback_pos: Option<GeoPosition>,
+        jetway_pos: Option<GeoPosition>,
+        #[serde(default, deserialize_with = "labels")]
+        pushbacklabels: Vec<String>,
+        #[serde(default)]
+        extra: HashMap<String, String>,
+    }
+
+    const STAND: &str = "[gate a 2]\ntype = 9\nmaxwingspan = 50.0\nhasjetway = 1\nloadertype = 3 3 2\nairlinecodes = \npushback_pos = 47.45 8.55 -173.9\npushbacklabels = Left|Right\nwalkertype = GsxJetwayPassenger\n";
+
+    #[test]
+    fn section_into_struct() {
+        let document = parse_document_str(STAND).document;
+        let stand: TestStand = from_section(&document, "gate a 2").unwrap();
+
+        assert_eq!(stand.parking_type, Some(9));
+        assert_eq!(stand.maxwingspan, Some(50.0));
+        assert!(stand.hasjetway);
+        assert_eq!(stand.loadertype, vec![3, 3, 2]);
+        assert_eq!(stand.airlinecodes, None);
+        assert_eq!(stand.pushback_pos.unwrap().heading, Some(-173.9));
+        assert_eq!(stand.jetway_pos, None);
+        assert_eq!(stand.pushbacklabels, vec!["Left", "Right"]);
+        assert_eq!(stand.extra.len(), 1);
+        assert_eq!(stand.extra["walkertype"], "GsxJetwayPassenger");
+    }
+
+    #[test]
+    fn errors_carry_section_and_key() {
+        let document =
+            parse_document_str(&STAND.replace("hasjetway = 1", "hasjetway = yes")).document;
+        let error = from_section::<TestStand>(&document, "gate a 2").unwrap_err();
+
+        assert_eq!(error.section.as_deref(), Some("gate a 2"));
+        assert_eq!(error.key.as_deref(), Some("hasjetway"));
+
+        let document = parse_document_str(
+            &STAND.replace("pushback_pos = 47.45 8.55 -173.9", "pushback_pos = 47.45"),
+        )
+        .document;
+        let error = from_section::<TestStand>(&document, "gate a 2").unwrap_err();
+        assert_eq!(error.section.as_deref(), Some("gate a 2"));
+        assert_eq!(error.key.as_deref(), Some("pushback_pos"));
+
+        let error = from_section::<TestStand>(&document, "gate a 3").unwrap_err();
+        assert_eq!(error.message, "section [gate a 3] not found");
+    }
+
+    #[test]
+    fn document_into_map() {
+        let document = parse_document_str(
+            "[general]\ncreator = A\n[gate a 2]\ntype = 9\n[general]\ncreator = B\n",
+        )
+        .document;
+        let sections: HashMap<String, HashMap<String, String>> = from_document(&document).unwrap();
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections["general"]["creator"], "B");
+        assert_eq!(sections["gate a 2"]["type"], "9");
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// A line starting with `[` that is not a `[name]` header.
+    MalformedSectionHeader,
+    /// A `[ ]` header without a name.
+    EmptySectionName,
+    /// A key/value line that appears before the first (valid) section header.
+    KeyOutsideSection,
+    /// A line whose key does not start with an ASCII letter and is not a comment.
+    InvalidKeyStart,
+    /// A line that looks like a key but has no `=`.
+    MissingEquals,
+    /// A value that contains another `=`.
+    MultipleEquals,
+    /// A key that was already set earlier in the same section. The later value wins.
+    DuplicateKey(String),
+    /// A section header that already appeared earlier. Both sections are read as one, with later
+    /// keys overriding earlier ones.
+    DuplicateSection(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column (in characters) the problem was found at.
+    pub column: usize,
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    pub fn new(line: usize, column: usize, kind: DiagnosticKind) -> Self {
+        Self {
+            line,
+            column,
+            severity: kind.severity(),
+            kind,
+        }
+    }
+}
+
+impl DiagnosticKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::DuplicateKey(_) | DiagnosticKind::DuplicateSection(_) => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::MalformedSectionHeader => write!(f, "malformed section header"),
+            DiagnosticKind::EmptySectionName => write!(f, "section header has no name"),
+            DiagnosticKind::KeyOutsideSection => write!(f, "key is not inside a valid section"),
+            DiagnosticKind::InvalidKeyStart => write!(f, "key does not start with a letter"),
+            DiagnosticKind::MissingEquals => write!(f, "line has no '='"),
+            DiagnosticKind::MultipleEquals => write!(f, "value contains another '='"),
+            DiagnosticKind::DuplicateKey(key) => write!(f, "key '{}' is set more than once", key),
+            DiagnosticKind::DuplicateSection(name) => {
+                write!(f, "section '{}' appears more than once", name)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.kind
+        )
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt;
+
+use geoutils::Location;
+
+use crate::{values::coordinates_of, IniDocument};
+
+/// What changed between two versions of a profile, compared section by section and key by key.
+/// Sections that appear more than once are compared the way GSX reads them, merged into one.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProfileDiff {
+    /// Sections only in the new profile, in its order.
+    pub added_sections: Vec<String>,
+    /// Sections only in the old profile, in its order.
+    pub removed_sections: Vec<String>,
+    /// Sections in both profiles whose keys differ, in the order of the new profile.
+    pub changed_sections: Vec<SectionDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionDiff {
+    pub name: String,
+    pub changes: Vec<KeyChange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyChange {
+    Added {
+        key: String,
+        value: String,
+    },
+    Removed {
+        key: String,
+        value: String,
+    },
+    Changed {
+        key: String,
+        old_value: String,
+        new_value: String,
+    },
+    /// A position whose latitude or longitude changed, moved by `distance` metres.
+    Moved {
+        key: String,
+        old_value: String,
+        new_value: String,
+        distance: f64,
+    },
+}
+
+impl KeyChange {
+    pub fn key(&self) -> &str {
+        match self {
+            KeyChange::Added { key, .. }
+            | KeyChange::Removed { key, .. }
+            | KeyChange::Changed { key, .. }
+            | KeyChange::Moved { key, .. } => key,
+        }
+    }
+}
+
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_sections.is_empty()
+            && self.removed_sections.is_empty()
+            && self.changed_sections.is_empty()
+    }
+
+    pub fn section(&self, name: &str) -> Option<&SectionDiff> {
+        self.changed_sections
+            .iter()
+            .find(|section| section.name == name)
+    }
+}
+
+pub fn diff_documents(old: &IniDocument, new: &IniDocument) -> ProfileDiff {
+    let old_names = old.section_names();
+    let new_names = new.section_names();
+
+    let mut diff = ProfileDiff::default();
+    for name in &new_names {
+        if !old_names.contains(name) {
+            diff.added_sections.push(String::from(*name));
+            continue;
+        }
+        let changes = diff_entries(&old.merged_entries(name), &new.merged_entries(name));
+        if !changes.is_empty() {
+            diff.changed_sections.push(SectionDiff {
+                name: String::from(*name),
+                changes,
+            });
+        }
+    }
+    diff.removed_sections = old_names
+        .into_iter()
+        .filter(|name| !new_names.contains(name))
+        .map(String::from)
+        .collect();
+    diff
+}
+
+fn diff_entries(old_entries: &[(&str, &str)], new_entries: &[(&str, &str)]) -> Vec<KeyChange> {
+    let old_value_of = |key: &str| {
+        old_entries
+            .iter()
+            .find(|(old_key, _)| *old_key == key)
+            .map(|(_, value)| *value)
+    };
+
+    let mut changes = vec![];
+    for (key, new_value) in new_entries {
+        let Some(old_value) = old_value_of(key) else {
+            changes.push(KeyChange::Added {
+                key: String::from(*key),
+                value: String::from(*new_value),
+            });
+            continue;
+        };
+        if old_value == *new_value {
+            continue;
+        }
+        let distance = match (coordinates_of(key, old_value), coordinates_of(key, new_value)) {
+            (Some(old_coordinates), Some(new_coordinates)) if old_coordinates != new_coordinates => {
+                Some(distance_between(old_coordinates, new_coordinates))
+            }
+            _ => None,
+        };
+        let (key, old_value, new_value) =
+            (String::from(*key), String::from(old_value), String::from(*new_value));
+        changes.push(match distance {
+            Some(distance) => KeyChange::Moved {
+                key,
+                old_value,
+                new_value,
+                distance,
+            },
+            None => KeyChange::Changed {
+                key,
+                old_value,
+                new_value,
+            },
+        });
+    }
+    for (key, old_value) in old_entries {
+        if !new_entries.iter().any(|(new_key, _)| new_key == key) {
+            changes.push(KeyChange::Removed {
+                key: String::from(*key),
+                value: String::from(*old_value),
+            });
+        }
+    }
+    changes
+}
+
+#[inline]
+fn distance_between(a: (f64, f64), b: (f64, f64)) -> f64 {
+    Location::new(a.0, a.1)
+        .haversine_distance_to(&Location::new(b.0, b.1))
+        .meters()
+}
+
+impl fmt::Display for KeyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyChange::Added { key, value } => write!(f, "+ {} = {}", key, value),
+            KeyChange::Removed { key, value } => write!(f, "- {} = {}", key, value),
+            KeyChange::Changed {
+                key,
+                old_value,
+                new_value,
+            } => write!(f, "~ {}: {} -> {}", key, old_value, new_value),
+            KeyChange::Moved {
+                key,
+                old_value,
+                new_value,
+                distance,
+            } => write!(
+                f,
+                "~ {}: {} -> {} (moved {:.1} m)",
+                key, old_value, new_value, distance
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ProfileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in &self.added_sections {
+            writeln!(f, "+ [{}]", name)?;
+        }
+        for name in &self.removed_sections {
+            writeln!(f, "- [{}]", name)?;
+        }
+        for section in &self.changed_sections {
+            writeln!(f, "~ [{}]", section.name)?;
+            for change in &section.changes {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_document, parse_document_str};
+
+    #[test]
+    fn identical_profiles_have_no_diff() {
+        let mut test_file_path = String::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push_str("/res/test/lszh-fsdt.ini");
+        let document = parse_document(test_file_path).unwrap().document;
+
+        assert!(diff_documents(&document, &document).is_empty());
+    }
+
+    #[test]
+    fn reports_sections_keys_and_moves() {
+        let old = parse_document_str(
+            "[general]\ncreator = A\n[gate a 1]\ntype = 9\nloadertype = 3 3 2\npushback_pos = 47.45 8.55 10\njetway_pos = 47.45 8.55 90\n[gate a 2]\ntype = 9\n",
+        )
+        .document;
+        let new = parse_document_str(
+            "[general]\ncreator = A\n[gate a 1]\ntype = 8\nloadertype = 3 3 3\npushback_pos = 47.4501 8.55 10\njetway_pos = 47.45 8.55 95\nairlinecodes = SWR\n[gate a 3]\ntype = 9\n",
+        )
+        .document;
+        let diff = diff_documents(&old, &new);
+
+        assert_eq!(diff.added_sections, vec!["gate a 3"]);
+        assert_eq!(diff.removed_sections, vec!["gate a 2"]);
+        assert_eq!(diff.changed_sections.len(), 1);
+        assert!(diff.section("general").is_none());
+
+        let changes = &diff.section("gate a 1").unwrap().changes;
+        let keys: Vec<&str> = changes.iter().map(KeyChange::key).collect();
+        assert_eq!(keys, vec!["type", "loadertype", "pushback_pos", "jetway_pos", "airlinecodes"]);
+        assert!(matches!(changes[1], KeyChange::Changed { .. }));
+        // Only the heading changed
+        assert!(matches!(changes[3], KeyChange::Changed { .. }));
+        let KeyChange::Moved { distance, .. } = changes[2] else {
+            panic!("pushback_pos should have moved");
+        };
+        assert!((distance - 11.1).abs() < 0.1);
+        assert_eq!(changes[4].to_string(), "+ airlinecodes = SWR");
+    }
+}
@@ -0,0 +1,703 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    io::{self, Write},
+    ops::Range,
+};
+
+use crate::{GSXIniFile, TextEncoding};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    /// Last line of a file that does not end with a newline.
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Comment,
+    SectionHeader { name: Range<usize> },
+    KeyValue { key: Range<usize>, value: Range<usize> },
+    /// A line the parser could not use. It is kept verbatim so it is written back unchanged.
+    Invalid,
+}
+
+/// Why an edit of an [`IniDocument`] was refused: the text would not be read back as it was given,
+/// e.g. a value with a line break would add lines the document doesn't know of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// Empty, with surrounding whitespace or a line break.
+    InvalidSectionName(String),
+    /// Not starting with a letter, with surrounding whitespace, a `=` or a line break.
+    InvalidKey(String),
+    /// With surrounding whitespace, a `=` or a line break.
+    InvalidValue(String),
+}
+
+/// A single line of an ini file. `key` and `value` ranges point into `text`, which holds the line
+/// exactly as it was read, without its line ending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    text: String,
+    ending: LineEnding,
+    kind: LineKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    name: String,
+    /// The header line followed by every line up to the next header.
+    lines: Vec<Line>,
+}
+
+/// An ini file that keeps every line, so it can be edited and written back without touching
+/// anything that was not edited.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IniDocument {
+    /// Lines before the first section header.
+    preamble: Vec<Line>,
+    sections: Vec<Section>,
+    encoding: TextEncoding,
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::InvalidSectionName(name) => write!(f, "invalid section name {:?}", name),
+            EditError::InvalidKey(key) => write!(f, "invalid key {:?}", key),
+            EditError::InvalidValue(value) => write!(f, "invalid value {:?}", value),
+        }
+    }
+}
+
+impl Error for EditError {}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::None => "",
+        }
+    }
+
+    /// Splits a raw line into its content and its line ending.
+    pub(crate) fn split(raw_line: &str) -> (&str, LineEnding) {
+        if let Some(line) = raw_line.strip_suffix("\r\n") {
+            (line, LineEnding::CrLf)
+        } else if let Some(line) = raw_line.strip_suffix('\n') {
+            (line, LineEnding::Lf)
+        } else {
+            (raw_line, LineEnding::None)
+        }
+    }
+}
+
+impl Line {
+    pub(crate) fn new(text: &str, ending: LineEnding, kind: LineKind) -> Self {
+        Self {
+            text: String::from(text),
+            ending,
+            kind,
+        }
+    }
+
+    fn new_key_value(key: &str, value: &str, ending: LineEnding) -> Self {
+        let text = format!("{} = {}", key, value);
+        let value_start = key.len() + 3;
+        Self {
+            kind: LineKind::KeyValue {
+                key: 0..key.len(),
+                value: value_start..text.len(),
+            },
+            text,
+            ending,
+        }
+    }
+
+    fn new_section_header(name: &str, ending: LineEnding) -> Self {
+        Self {
+            text: format!("[{}]", name),
+            ending,
+            kind: LineKind::SectionHeader {
+                name: 1..name.len() + 1,
+            },
+        }
+    }
+
+    fn new_blank(ending: LineEnding) -> Self {
+        Self {
+            text: String::new(),
+            ending,
+            kind: LineKind::Blank,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    pub fn kind(&self) -> &LineKind {
+        &self.kind
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::KeyValue { key, .. } => Some(&self.text[key.clone()]),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::KeyValue { value, .. } => Some(&self.text[value.clone()]),
+            _ => None,
+        }
+    }
+
+    fn set_value(&mut self, new_value: &str) {
+        if let LineKind::KeyValue { value, .. } = &mut self.kind {
+            self.text.replace_range(value.clone(), new_value);
+            value.end = value.start + new_value.len();
+        }
+    }
+
+    fn set_section_name(&mut self, new_name: &str) {
+        if let LineKind::SectionHeader { name } = &mut self.kind {
+            self.text.replace_range(name.clone(), new_name);
+            name.end = name.start + new_name.len();
+        }
+    }
+}
+
+impl Section {
+    pub(crate) fn new(name: String, header: Line) -> Self {
+        Self {
+            name,
+            lines: vec![header],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// All lines of the section, starting with the header.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Key/value pairs in file order. A key that is set more than once is returned every time.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines
+            .iter()
+            .filter_map(|line| Some((line.key()?, line.value()?)))
+    }
+
+    /// Value of `key`. If the key is set more than once the last value wins.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries()
+            .filter(|(entry_key, _)| *entry_key == key)
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub(crate) fn push_line(&mut self, line: Line) {
+        self.lines.push(line);
+    }
+
+    fn set(&mut self, key: &str, value: &str, newline: LineEnding) {
+        if let Some(line) = self
+            .lines
+            .iter_mut()
+            .rev()
+            .find(|line| line.key() == Some(key))
+        {
+            line.set_value(value);
+            return;
+        }
+
+        // New keys go after the last key of the section, so trailing blank lines and comments stay
+        // in front of the next header
+        let insert_index = self
+            .lines
+            .iter()
+            .rposition(|line| line.key().is_some())
+            .unwrap_or(0)
+            + 1;
+        insert_line(
+            &mut self.lines,
+            insert_index,
+            Line::new_key_value(key, value, newline),
+        );
+    }
+
+    fn remove(&mut self, key: &str) -> Option<String> {
+        let removed_value = self.get(key).map(String::from);
+        if removed_value.is_some() {
+            let last_index = self.lines.len() - 1;
+            let last_ending = self.lines[last_index].ending;
+            self.lines.retain(|line| line.key() != Some(key));
+            // Keep the missing newline at the end of the file if the last line was removed
+            if let Some(last_line) = self.lines.last_mut() {
+                if last_ending == LineEnding::None {
+                    last_line.ending = LineEnding::None;
+                }
+            }
+        }
+        removed_value
+    }
+}
+
+impl IniDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Encoding used by [`IniDocument::write_to`].
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.encoding = encoding;
+    }
+
+    pub fn preamble(&self) -> &[Line] {
+        &self.preamble
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// The first section called `name`.
+    ///
+    /// A file can contain the same header more than once. GSX reads such sections as one, with
+    /// later keys overriding earlier ones, and so do [`IniDocument::get`], [`IniDocument::set`],
+    /// [`IniDocument::remove`] and [`IniDocument::to_map`].
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Every section called `name` in file order.
+    pub fn sections_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Section> {
+        self.sections
+            .iter()
+            .filter(move |section| section.name == name)
+    }
+
+    /// Name of every section once, in the order they first appear.
+    pub fn section_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        for section in &self.sections {
+            if !names.contains(&section.name.as_str()) {
+                names.push(&section.name);
+            }
+        }
+        names
+    }
+
+    /// Key/value pairs of all sections called `section_name` read as one. Later keys override
+    /// earlier ones but keep the position of their first occurrence.
+    pub fn merged_entries(&self, section_name: &str) -> Vec<(&str, &str)> {
+        let mut entries: Vec<(&str, &str)> = vec![];
+        let mut entry_indices: HashMap<&str, usize> = HashMap::new();
+        for section in self.sections.iter().filter(|section| section.name == section_name) {
+            for (key, value) in section.entries() {
+                match entry_indices.get(key) {
+                    Some(index) => entries[*index].1 = value,
+                    None => {
+                        entry_indices.insert(key, entries.len());
+                        entries.push((key, value));
+                    }
+                }
+            }
+        }
+        entries
+    }
+
+    /// Value of `key` in `section_name`, looking through all sections of that name.
+    pub fn get(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .rev()
+            .filter(|section| section.name == section_name)
+            .find_map(|section| section.get(key))
+    }
+
+    /// Sets `key` in `section_name`, creating the section if it doesn't exist yet. An existing key
+    /// keeps its position and spacing, only the value is replaced.
+    pub fn set(&mut self, section_name: &str, key: &str, value: &str) -> Result<(), EditError> {
+        check_section_name(section_name)?;
+        let is_key_start = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+        if !is_key_start || !is_single_line(key) || key.contains('=') {
+            return Err(EditError::InvalidKey(String::from(key)));
+        }
+        if !is_single_line(value) || value.contains('=') {
+            return Err(EditError::InvalidValue(String::from(value)));
+        }
+
+        let newline = self.newline();
+        if self.section(section_name).is_none() {
+            self.add_section(section_name)?;
+        }
+        // The line that is in effect gets the new value. New keys go into the last section of
+        // that name.
+        let section = match self
+            .sections
+            .iter()
+            .rposition(|section| section.name == section_name && section.contains_key(key))
+        {
+            Some(index) => &mut self.sections[index],
+            None => self.section_mut(section_name).unwrap(),
+        };
+        section.set(key, value, newline);
+        Ok(())
+    }
+
+    /// Removes every occurrence of `key` in `section_name` and returns the value that was in effect.
+    pub fn remove(&mut self, section_name: &str, key: &str) -> Option<String> {
+        let removed_value = self.get(section_name, key).map(String::from);
+        for section in self
+            .sections
+            .iter_mut()
+            .filter(|section| section.name == section_name)
+        {
+            section.remove(key);
+        }
+        removed_value
+    }
+
+    /// Appends a new, empty section. Returns false if a section with that name already exists.
+    pub fn add_section(&mut self, name: &str) -> Result<bool, EditError> {
+        check_section_name(name)?;
+        if self.section(name).is_some() {
+            return Ok(false);
+        }
+        let newline = self.newline();
+
+        let last_lines = match self.sections.last_mut() {
+            Some(section) => &mut section.lines,
+            None => &mut self.preamble,
+        };
+        if let Some(last_line) = last_lines.last_mut() {
+            if last_line.ending == LineEnding::None {
+                last_line.ending = newline;
+            }
+            if last_line.kind != LineKind::Blank {
+                last_lines.push(Line::new_blank(newline));
+            }
+        }
+
+        self.sections.push(Section::new(
+            String::from(name),
+            Line::new_section_header(name, newline),
+        ));
+        Ok(true)
+    }
+
+    /// Renames every section called `old_name`. Returns false if there is no such section or if
+    /// `new_name` is already taken.
+    pub fn rename_section(&mut self, old_name: &str, new_name: &str) -> Result<bool, EditError> {
+        check_section_name(new_name)?;
+        if self.section(new_name).is_some() || self.section(old_name).is_none() {
+            return Ok(false);
+        }
+        for section in self
+            .sections
+            .iter_mut()
+            .filter(|section| section.name == old_name)
+        {
+            section.name = String::from(new_name);
+            section.lines[0].set_section_name(new_name);
+        }
+        Ok(true)
+    }
+
+    /// Removes every section called `name` together with every line up to the next header.
+    pub fn remove_section(&mut self, name: &str) -> Vec<Section> {
+        let ended_without_newline = self
+            .lines()
+            .last()
+            .is_some_and(|line| line.ending == LineEnding::None);
+
+        let (removed_sections, kept_sections) = self
+            .sections
+            .drain(..)
+            .partition(|section| section.name == name);
+        self.sections = kept_sections;
+
+        if ended_without_newline {
+            let last_lines = match self.sections.last_mut() {
+                Some(section) => &mut section.lines,
+                None => &mut self.preamble,
+            };
+            if let Some(last_line) = last_lines.last_mut() {
+                last_line.ending = LineEnding::None;
+            }
+        }
+        removed_sections
+    }
+
+    /// Every line of the document in file order.
+    pub fn lines(&self) -> impl Iterator<Item = &Line> {
+        self.preamble
+            .iter()
+            .chain(self.sections.iter().flat_map(|section| section.lines.iter()))
+    }
+
+    /// Writes the document in the encoding it was read with.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.encoding.encode(&self.to_string()))
+    }
+
+    /// The section/key map `parse_file` returns. Sections with the same name are merged.
+    pub fn to_map(&self) -> GSXIniFile {
+        let mut ini_file = GSXIniFile::new();
+        for section in self.sections.iter() {
+            let values: &mut HashMap<String, String> =
+                ini_file.entry(section.name.clone()).or_default();
+            for (key, value) in section.entries() {
+                values.insert(String::from(key), String::from(value));
+            }
+        }
+        ini_file
+    }
+
+    pub(crate) fn push_line(&mut self, line: Line) {
+        match self.sections.last_mut() {
+            Some(section) => section.push_line(line),
+            None => self.preamble.push(line),
+        }
+    }
+
+    pub(crate) fn push_section(&mut self, section: Section) {
+        self.sections.push(section);
+    }
+
+    /// The last section called `name`.
+    fn section_mut(&mut self, name: &str) -> Option<&mut Section> {
+        self.sections
+            .iter_mut()
+            .rev()
+            .find(|section| section.name == name)
+    }
+
+    /// Line ending used for new lines: the one the file already uses, `\n` otherwise.
+    pub(crate) fn newline(&self) -> LineEnding {
+        self.lines()
+            .map(|line| line.ending)
+            .find(|ending| *ending != LineEnding::None)
+            .unwrap_or(LineEnding::Lf)
+    }
+}
+
+/// Section names are trimmed when read, so surrounding whitespace would be lost.
+fn check_section_name(name: &str) -> Result<(), EditError> {
+    if name.is_empty() || !is_single_line(name) {
+        return Err(EditError::InvalidSectionName(String::from(name)));
+    }
+    Ok(())
+}
+
+/// No line break and nothing that would be trimmed when the line is read back.
+fn is_single_line(text: &str) -> bool {
+    !text.contains(['\n', '\r']) && text.trim() == text
+}
+
+impl fmt::Display for IniDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines() {
+            write!(f, "{}{}", line.text, line.ending.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Inserts a line, moving a missing trailing newline from the previous line to the new one.
+fn insert_line(lines: &mut Vec<Line>, index: usize, mut line: Line) {
+    if let Some(previous_line) = lines.get_mut(index - 1) {
+        if previous_line.ending == LineEnding::None {
+            previous_line.ending = line.ending;
+            line.ending = LineEnding::None;
+        }
+    }
+    lines.insert(index, line);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    fn parse(text: &str) -> IniDocument {
+        crate::parse_document_str(text).document
+    }
+
+    #[test]
+    fn unedited_document_is_written_back_unchanged() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("res/test/lszh-fsdt.ini");
+        let text = fs::read_to_string(&path).unwrap();
+
+        let document = crate::parse_document(&path).unwrap().document;
+        let mut written = vec![];
+        document.write_to(&mut written).unwrap();
+
+        assert_eq!(String::from_utf8(written).unwrap(), text);
+    }
+
+    #[test]
+    fn trivia_is_preserved() {
+        let text = "; leading comment\r\n\r\n[general]\r\n  creator   =  Someone  \r\n# comment\r\n_invalid\r\n[ gate a 2 ]\r\ntype=9";
+        let document = parse(text);
+
+        assert_eq!(document.to_string(), text);
+        assert_eq!(document.preamble().len(), 2);
+        assert_eq!(document.sections().len(), 2);
+        assert_eq!(document.get("general", "creator"), Some("Someone"));
+        assert_eq!(document.get("gate a 2", "type"), Some("9"));
+    }
+
+    #[test]
+    fn set_existing_key_keeps_spacing() {
+        let mut document = parse("[general]\ncreator   =  Someone  \nafcad_path = x\n");
+        document.set("general", "creator", "Someone else").unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "[general]\ncreator   =  Someone else  \nafcad_path = x\n"
+        );
+    }
+
+    #[test]
+    fn set_new_key_goes_after_last_key() {
+        let mut document = parse("[gate a 2]\ntype = 9\n\n[gate a 3]\ntype = 9");
+        document.set("gate a 2", "pushback", "2").unwrap();
+        document.set("gate a 3", "pushback", "3").unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "[gate a 2]\ntype = 9\npushback = 2\n\n[gate a 3]\ntype = 9\npushback = 3"
+        );
+    }
+
+    #[test]
+    fn new_lines_use_existing_line_ending() {
+        let mut document = parse("[general]\r\ncreator = Someone\r\n");
+        document.set("gate a 2", "type", "9").unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "[general]\r\ncreator = Someone\r\n\r\n[gate a 2]\r\ntype = 9\r\n"
+        );
+    }
+
+    #[test]
+    fn remove_key() {
+        let mut document = parse("[general]\ncreator = A\nafcad_path = x\ncreator = B");
+
+        assert_eq!(document.remove("general", "creator"), Some(String::from("B")));
+        assert_eq!(document.remove("general", "creator"), None);
+        assert_eq!(document.remove("missing", "creator"), None);
+        assert_eq!(document.to_string(), "[general]\nafcad_path = x");
+    }
+
+    #[test]
+    fn add_rename_and_remove_sections() {
+        let mut document = parse("[general]\ncreator = A\n\n[gate a 2]\ntype = 9\n");
+
+        assert!(document.add_section("gate a 3").unwrap());
+        assert!(!document.add_section("gate a 3").unwrap());
+        assert!(document.rename_section("gate a 2", "gate a 4").unwrap());
+        assert!(!document.rename_section("gate a 4", "general").unwrap());
+        assert!(!document.rename_section("missing", "gate a 5").unwrap());
+        assert_eq!(document.remove_section("general").len(), 1);
+        assert!(document.remove_section("general").is_empty());
+
+        assert_eq!(document.to_string(), "[gate a 4]\ntype = 9\n\n[gate a 3]\n");
+        assert!(document.section("gate a 4").unwrap().contains_key("type"));
+    }
+
+    #[test]
+    fn edits_that_would_add_lines_are_refused() {
+        let text = "[general]\ncreator = A\n";
+        let mut document = parse(text);
+
+        assert_eq!(
+            document.set("general", "creator", "A\n[gate a 2]"),
+            Err(EditError::InvalidValue(String::from("A\n[gate a 2]")))
+        );
+        assert!(document.set("general", "creator", "A\r").is_err());
+        assert!(document.set("general", "creator", "A = B").is_err());
+        assert_eq!(
+            document.set("general", "type = 9\ncreator", "A"),
+            Err(EditError::InvalidKey(String::from("type = 9\ncreator")))
+        );
+        assert!(document.set("general", "type=9", "A").is_err());
+        assert!(document.set("general", "; creator", "A").is_err());
+        assert!(document.set("general", "", "A").is_err());
+        assert!(document.set("gate a 2]\n[gate a 3", "type", "9").is_err());
+        assert!(document.add_section("gate a 2]\n[gate a 3").is_err());
+        assert!(document.add_section(" gate a 2").is_err());
+        assert!(document.add_section("").is_err());
+        assert!(document.rename_section("general", "general\r").is_err());
+        assert_eq!(document.to_string(), text);
+
+        document.set("general", "creator", "").unwrap();
+        assert_eq!(parse(&document.to_string()).get("general", "creator"), Some(""));
+    }
+
+    #[test]
+    fn remove_last_section_keeps_missing_newline() {
+        let mut document = parse("[general]\ncreator = A\n[gate a 2]\ntype = 9");
+        document.remove_section("gate a 2");
+
+        assert_eq!(document.to_string(), "[general]\ncreator = A");
+    }
+
+    #[test]
+    fn duplicate_sections_are_read_as_one() {
+        let text = "[general]\ncreator = A\nafcad_path = x\n\n[gate a 2]\ntype = 9\n\n[general]\ncreator = B\ndeice_areas = []\n";
+        let mut document = parse(text);
+
+        assert_eq!(document.to_string(), text);
+        assert_eq!(document.sections_named("general").count(), 2);
+        assert_eq!(document.get("general", "creator"), Some("B"));
+        assert_eq!(document.get("general", "afcad_path"), Some("x"));
+
+        let ini_file = document.to_map();
+        assert_eq!(ini_file["general"].len(), 3);
+        assert_eq!(ini_file["general"]["creator"], "B");
+
+        document.set("general", "creator", "C").unwrap();
+        document.set("general", "afcad_path", "y").unwrap();
+        document.set("general", "deice_labels", "z").unwrap();
+        assert_eq!(
+            document.to_string(),
+            "[general]\ncreator = A\nafcad_path = y\n\n[gate a 2]\ntype = 9\n\n[general]\ncreator = C\ndeice_areas = []\ndeice_labels = z\n"
+        );
+
+        assert_eq!(document.remove("general", "creator"), Some(String::from("C")));
+        assert_eq!(document.get("general", "creator"), None);
+
+        assert!(document.rename_section("general", "settings").unwrap());
+        assert_eq!(document.sections_named("settings").count(), 2);
+        assert_eq!(document.remove_section("settings").len(), 2);
+        assert_eq!(document.to_string(), "[gate a 2]\ntype = 9\n\n");
+    }
+}
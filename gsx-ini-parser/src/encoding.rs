@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+
+use encoding_rs::{UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Encoding a profile was read with. Profiles written by Windows tools are not always UTF-8, so
+/// the encoding is kept to write them back the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le { bom: bool },
+    Utf16Be { bom: bool },
+    Windows1252,
+}
+
+impl TextEncoding {
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(UTF8_BOM) {
+            return TextEncoding::Utf8Bom;
+        }
+        if bytes.starts_with(UTF16LE_BOM) {
+            return TextEncoding::Utf16Le { bom: true };
+        }
+        if bytes.starts_with(UTF16BE_BOM) {
+            return TextEncoding::Utf16Be { bom: true };
+        }
+        if let Some(encoding) = detect_utf16_without_bom(bytes) {
+            return encoding;
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            TextEncoding::Utf8
+        } else {
+            TextEncoding::Windows1252
+        }
+    }
+
+    /// Decodes the bytes, dropping the byte order mark if there is one. Valid UTF-8 is borrowed
+    /// instead of copied.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        let bytes = &bytes[self.bom().len().min(bytes.len())..];
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => String::from_utf8_lossy(bytes),
+            TextEncoding::Utf16Le { .. } => UTF_16LE.decode_without_bom_handling(bytes).0,
+            TextEncoding::Utf16Be { .. } => UTF_16BE.decode_without_bom_handling(bytes).0,
+            TextEncoding::Windows1252 => WINDOWS_1252.decode_without_bom_handling(bytes).0,
+        }
+    }
+
+    /// Encodes the text, including the byte order mark if the original file had one. Characters
+    /// that don't exist in Windows-1252 are written as HTML character references.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let mut bytes = self.bom().to_vec();
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => bytes.extend_from_slice(text.as_bytes()),
+            TextEncoding::Utf16Le { .. } => {
+                for unit in text.encode_utf16() {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            TextEncoding::Utf16Be { .. } => {
+                for unit in text.encode_utf16() {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            TextEncoding::Windows1252 => bytes.extend_from_slice(&WINDOWS_1252.encode(text).0),
+        }
+        bytes
+    }
+
+    fn bom(&self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8Bom => UTF8_BOM,
+            TextEncoding::Utf16Le { bom: true } => UTF16LE_BOM,
+            TextEncoding::Utf16Be { bom: true } => UTF16BE_BOM,
+            _ => &[],
+        }
+    }
+}
+
+/// Profiles are almost entirely ASCII, so UTF-16 without a BOM shows up as a zero byte in every
+/// other position.
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let zero_high = bytes.chunks(2).filter(|pair| pair[1] == 0).count();
+    let zero_low = bytes.chunks(2).filter(|pair| pair[0] == 0).count();
+    if zero_high * 10 >= pairs * 9 {
+        Some(TextEncoding::Utf16Le { bom: false })
+    } else if zero_low * 10 >= pairs * 9 {
+        Some(TextEncoding::Utf16Be { bom: false })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "[general]\r\ncreator = Jürgen\r\n";
+
+    #[test]
+    fn detects_and_round_trips_every_encoding() {
+        let encodings = [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16Le { bom: true },
+            TextEncoding::Utf16Be { bom: true },
+            TextEncoding::Utf16Le { bom: false },
+            TextEncoding::Utf16Be { bom: false },
+            TextEncoding::Windows1252,
+        ];
+        for encoding in encodings {
+            let bytes = encoding.encode(TEXT);
+            assert_eq!(TextEncoding::detect(&bytes), encoding);
+            assert_eq!(encoding.decode(&bytes), TEXT);
+        }
+    }
+
+    #[test]
+    fn windows_1252_is_decoded() {
+        let bytes = b"creator = J\xfcrgen \x80";
+        assert_eq!(TextEncoding::detect(bytes), TextEncoding::Windows1252);
+        assert_eq!(TextEncoding::Windows1252.decode(bytes), "creator = Jürgen €");
+    }
+
+    #[test]
+    fn empty_input_is_utf8() {
+        assert_eq!(TextEncoding::detect(b""), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::Utf8Bom.decode(b""), "");
+    }
+}
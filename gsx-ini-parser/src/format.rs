@@ -0,0 +1,342 @@
+use std::cmp::Ordering;
+
+use crate::{
+    values::{coordinates_of, list_coordinate_spans},
+    IniDocument, Line, LineEnding, LineKind, Section,
+};
+
+/// How [`format_document`] normalizes a profile. The default only changes whitespace and line
+/// endings, so the formatted file reads exactly like the original.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FormatOptions {
+    /// Line ending for every line. `None` uses the one the file already uses.
+    pub line_ending: Option<LineEnding>,
+    /// Puts `[general]` first and the stands after it in natural order (`gate a 2` before
+    /// `gate a 10`). Sections of the same name keep their order, so they still merge the same way.
+    pub sort_sections: bool,
+    /// Number of decimals latitude and longitude of positions are written with.
+    pub coordinate_precision: Option<usize>,
+}
+
+/// Rewrites the document in its canonical form:
+///
+/// - `key = value` on every key line, `key =` if the value is empty
+/// - no leading or trailing whitespace on any line
+/// - the same line ending on every line, including the last one
+/// - exactly one blank line between sections and none at the end of the file
+/// - positions separated by single spaces, with `coordinate_precision` decimals if it is set
+/// - the coordinates in `deice_areas` and `pushbackaddpos` with `coordinate_precision` decimals
+///
+/// Returns whether anything changed.
+pub fn format_document(document: &mut IniDocument, options: &FormatOptions) -> bool {
+    let formatted = formatted(document, options);
+    let changed = formatted != *document;
+    *document = formatted;
+    changed
+}
+
+/// Check-only mode of [`format_document`]: whether the document is already canonical.
+pub fn is_canonical(document: &IniDocument, options: &FormatOptions) -> bool {
+    formatted(document, options) == *document
+}
+
+fn formatted(document: &IniDocument, options: &FormatOptions) -> IniDocument {
+    let newline = match options.line_ending {
+        Some(LineEnding::None) | None => document.newline(),
+        Some(line_ending) => line_ending,
+    };
+
+    let mut sections: Vec<&Section> = document.sections().iter().collect();
+    if options.sort_sections {
+        sections.sort_by(|a, b| compare_section_names(a.name(), b.name()));
+    }
+
+    let mut formatted = IniDocument::new();
+    formatted.set_encoding(document.encoding());
+
+    let mut preamble = format_lines(document.preamble(), newline, options);
+    if !preamble.is_empty() && !sections.is_empty() {
+        preamble.push(blank_line(newline));
+    }
+    for line in preamble {
+        formatted.push_line(line);
+    }
+
+    for (index, section) in sections.iter().enumerate() {
+        let name = section.name();
+        let header = Line::new(
+            &format!("[{}]", name),
+            newline,
+            LineKind::SectionHeader {
+                name: 1..name.len() + 1,
+            },
+        );
+        formatted.push_section(Section::new(String::from(name), header));
+
+        let mut lines = format_lines(&section.lines()[1..], newline, options);
+        if index + 1 < sections.len() {
+            lines.push(blank_line(newline));
+        }
+        for line in lines {
+            formatted.push_line(line);
+        }
+    }
+
+    formatted
+}
+
+/// Formats the lines of a section body or the preamble, without trailing blank lines.
+fn format_lines(lines: &[Line], newline: LineEnding, options: &FormatOptions) -> Vec<Line> {
+    let mut formatted: Vec<Line> = lines
+        .iter()
+        .map(|line| format_line(line, newline, options))
+        .collect();
+    while formatted
+        .last()
+        .is_some_and(|line| *line.kind() == LineKind::Blank)
+    {
+        formatted.pop();
+    }
+    formatted
+}
+
+fn format_line(line: &Line, newline: LineEnding, options: &FormatOptions) -> Line {
+    match line.kind() {
+        LineKind::KeyValue { .. } => {
+            let key = line.key().unwrap();
+            let value = line.value().unwrap();
+            let value = normalize_position(key, value, options.coordinate_precision)
+                .unwrap_or_else(|| String::from(value));
+            let text = if value.is_empty() {
+                format!("{} =", key)
+            } else {
+                format!("{} = {}", key, value)
+            };
+            let value_start = text.len() - value.len();
+            Line::new(
+                &text,
+                newline,
+                LineKind::KeyValue {
+                    key: 0..key.len(),
+                    value: value_start..text.len(),
+                },
+            )
+        }
+        LineKind::Blank => blank_line(newline),
+        // Trimming could turn a broken header into a real one and change what GSX reads
+        LineKind::Invalid => Line::new(line.text(), newline, LineKind::Invalid),
+        kind => Line::new(line.text().trim(), newline, kind.clone()),
+    }
+}
+
+fn blank_line(newline: LineEnding) -> Line {
+    Line::new("", newline, LineKind::Blank)
+}
+
+/// Position values like `pushback_pos` with single spaces and rounded coordinates, lists of
+/// positions with rounded coordinates. Other values, like `loadertype = 3 3 2`, are left alone
+/// even though they look like a position.
+fn normalize_position(key: &str, value: &str, precision: Option<usize>) -> Option<String> {
+    if coordinates_of(key, value).is_none() {
+        return round_list_coordinates(key, value, precision?);
+    }
+    let normalized: Vec<String> = value
+        .split_whitespace()
+        .enumerate()
+        .map(|(index, token)| match precision {
+            Some(precision) if index < 2 => format!("{:.*}", precision, token.parse::<f64>().unwrap()),
+            _ => String::from(token),
+        })
+        .collect();
+    Some(normalized.join(" "))
+}
+
+/// Rounds the coordinates of `deice_areas` and `pushbackaddpos`, the rest of the list stays as it
+/// is written.
+fn round_list_coordinates(key: &str, value: &str, precision: usize) -> Option<String> {
+    let mut rounded = String::new();
+    let mut end = 0;
+    for span in list_coordinate_spans(key, value)? {
+        rounded.push_str(&value[end..span.start]);
+        let coordinate: f64 = value[span.clone()].parse().ok()?;
+        rounded.push_str(&format!("{:.*}", precision, coordinate));
+        end = span.end;
+    }
+    rounded.push_str(&value[end..]);
+    Some(rounded)
+}
+
+fn compare_section_names(a: &str, b: &str) -> Ordering {
+    match (a == "general", b == "general") {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        _ => natural_cmp(a, b),
+    }
+}
+
+/// Compares runs of digits by their value, everything else character by character.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                let mut a_number = String::new();
+                while let Some(digit) = a_chars.next_if(char::is_ascii_digit) {
+                    a_number.push(digit);
+                }
+                let mut b_number = String::new();
+                while let Some(digit) = b_chars.next_if(char::is_ascii_digit) {
+                    b_number.push(digit);
+                }
+                let a_number = a_number.trim_start_matches('0');
+                let b_number = b_number.trim_start_matches('0');
+                let ordering = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(b_number));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(a_char), Some(b_char)) => {
+                if a_char != b_char {
+                    return a_char.cmp(&b_char);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_document, parse_document_str};
+
+    const MESSY: &str = "[gate a 10]\r\ntype=9\r\n  airlinecodes =   \r\n\r\n\r\n[general]\n; comment   \ncreator   =  Someone\n[gate a 2]\npushback_pos = 47.4535553199711    8.558652269898 -173.983079910278\nloadertype = 3 3 2";
+
+    #[test]
+    fn formats_whitespace_and_newlines() {
+        let mut document = parse_document_str(MESSY).document;
+        assert!(!is_canonical(&document, &FormatOptions::default()));
+        assert!(format_document(&mut document, &FormatOptions::default()));
+
+        assert_eq!(
+            document.to_string(),
+            "[gate a 10]\r\ntype = 9\r\nairlinecodes =\r\n\r\n[general]\r\n; comment\r\ncreator = Someone\r\n\r\n[gate a 2]\r\npushback_pos = 47.4535553199711 8.558652269898 -173.983079910278\r\nloadertype = 3 3 2\r\n"
+        );
+        assert!(is_canonical(&document, &FormatOptions::default()));
+        assert!(!format_document(&mut document, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn sorts_sections_and_rounds_coordinates() {
+        let mut document = parse_document_str(MESSY).document;
+        let options = FormatOptions {
+            line_ending: Some(LineEnding::Lf),
+            sort_sections: true,
+            coordinate_precision: Some(6),
+        };
+        format_document(&mut document, &options);
+
+        assert_eq!(
+            document.to_string(),
+            "[general]\n; comment\ncreator = Someone\n\n[gate a 2]\npushback_pos = 47.453555 8.558652 -173.983079910278\nloadertype = 3 3 2\n\n[gate a 10]\ntype = 9\nairlinecodes =\n"
+        );
+        assert!(is_canonical(&document, &options));
+    }
+
+    #[test]
+    fn rounds_deice_area_coordinates() {
+        let options = FormatOptions {
+            coordinate_precision: Some(6),
+            ..Default::default()
+        };
+        let text = "[general]\ndeice_areas = [(47.460101708793765, 8.561248453982142, 96, 20, \"Rwy 10 – C1\",\"VgdsDeIceWall\"),(47.46614446609993, 8.54822540052842, 334, 20, \"Rwy 16-F3\", \"VgdsDeIceWall\")]\n";
+        let mut document = parse_document_str(text).document;
+        assert!(!is_canonical(&document, &options));
+        format_document(&mut document, &options);
+
+        assert_eq!(
+            document.get("general", "deice_areas"),
+            Some("[(47.460102, 8.561248, 96, 20, \"Rwy 10 – C1\",\"VgdsDeIceWall\"),(47.466144, 8.548225, 334, 20, \"Rwy 16-F3\", \"VgdsDeIceWall\")]")
+        );
+        assert!(is_canonical(&document, &options));
+        // Without a precision the list is left as it is
+        assert!(is_canonical(&parse_document_str(text).document, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn rounds_pushback_add_position_coordinates() {
+        let options = FormatOptions {
+            coordinate_precision: Some(6),
+            ..Default::default()
+        };
+        let text = "[gate a 1]\npushbackaddpos = [{'snap': False, 'approach': [(47.45355531997, 8.5586522698, 10.5), None], 'pos': (47.4531234567, 8.5581234567, -170.123), 'label': u'Tail left'}]\n";
+        let mut document = parse_document_str(text).document;
+        assert!(!is_canonical(&document, &options));
+        format_document(&mut document, &options);
+
+        assert_eq!(
+            document.get("gate a 1", "pushbackaddpos"),
+            Some("[{'snap': False, 'approach': [(47.453555, 8.558652, 10.5), None], 'pos': (47.453123, 8.558123, -170.123), 'label': u'Tail left'}]")
+        );
+        assert!(is_canonical(&document, &options));
+
+        // Lists the decoder doesn't accept are left alone
+        let broken = "[gate a 1]\npushbackaddpos = [{'pos': (47.4531234567, 8.5581234567, -170.123)\n";
+        assert!(is_canonical(&parse_document_str(broken).document, &options));
+    }
+
+    #[test]
+    fn keeps_invalid_lines_as_they_are() {
+        let text = "[general]\ncreator = Someone\n [gate a 2]\ntype = 9\n[gate a 3] x\n\tnot a key  \n";
+        let invalid_lines = |document: &IniDocument| -> Vec<String> {
+            document
+                .lines()
+                .filter(|line| *line.kind() == LineKind::Invalid)
+                .map(|line| String::from(line.text()))
+                .collect()
+        };
+        let parsed = parse_document_str(text).document;
+        // Keys after a broken header belong to no section
+        assert_eq!(invalid_lines(&parsed), vec![" [gate a 2]", "type = 9", "[gate a 3] x", "\tnot a key  "]);
+
+        let mut document = parsed.clone();
+        format_document(&mut document, &FormatOptions::default());
+        let reparsed = parse_document_str(&document.to_string()).document;
+        assert_eq!(reparsed.section_names(), parsed.section_names());
+        assert_eq!(reparsed.section_names(), vec!["general"]);
+        assert_eq!(invalid_lines(&reparsed), invalid_lines(&parsed));
+        assert!(is_canonical(&reparsed, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn formatting_keeps_the_meaning() {
+        let mut test_file_path = String::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push_str("/res/test/lszh-fsdt.ini");
+        let original = parse_document(test_file_path).unwrap().document;
+
+        let mut document = original.clone();
+        let options = FormatOptions {
+            sort_sections: true,
+            ..Default::default()
+        };
+        format_document(&mut document, &options);
+
+        assert_eq!(document.to_map(), original.to_map());
+        assert_eq!(parse_document_str(&document.to_string()).document, document);
+    }
+
+    #[test]
+    fn natural_section_order() {
+        let mut names = vec!["gate b 1", "gate a 10", "general", "gate a 2", "gate a 02a"];
+        names.sort_by(|a, b| compare_section_names(a, b));
+        assert_eq!(names, vec!["general", "gate a 2", "gate a 02a", "gate a 10", "gate b 1"]);
+    }
+}
@@ -1,66 +1,149 @@
-use std::{collections::HashMap, fs::File, io::{self, BufRead}};
+use std::{collections::{HashMap, HashSet}, fs, io::{self, Read}, path::Path};
 
-use regex::Regex;
+pub mod de;
+mod diagnostic;
+mod diff;
+mod document;
+mod encoding;
+mod format;
+mod merge;
+mod profile;
+mod stand_id;
+mod tokenizer;
+mod values;
+
+pub use de::{from_document, from_section, DeError};
+
+pub use diagnostic::{Diagnostic, DiagnosticKind, Severity};
+pub use diff::{diff_documents, KeyChange, ProfileDiff, SectionDiff};
+pub use document::{EditError, IniDocument, Line, LineEnding, LineKind, Section};
+pub use encoding::TextEncoding;
+pub use format::{format_document, is_canonical, FormatOptions};
+pub use merge::{merge_documents, MergeConflict, MergeResult, Resolution};
+pub use profile::{General, GsxProfileDocument, PushbackKind, PushbackPath, Stand};
+pub use stand_id::{compare_stand_names, StandId, StandIdError, StandKind};
+pub use tokenizer::{Token, Tokenizer};
+pub use values::{
+    is_position_key, parse_airline_codes, parse_bool, parse_deice_areas, parse_int_list,
+    parse_labels, parse_object_position, parse_position, parse_pushback_add_positions,
+    parse_tuple_list, DeiceArea, GeoPosition, ObjectPosition, PushbackAddPosition, TupleValue,
+    ValueError, ValueErrorKind,
+};
 
 type GSXIniFile = HashMap<String, HashMap<String, String>>;
 
-pub fn parse_file(path: &str) -> io::Result<GSXIniFile> {
-    let mut ini_file = GSXIniFile::new();
+/// Result of a parse that also reports every line the parser could not use as-is.
+#[derive(Debug, Clone)]
+pub struct ParsedIniFile {
+    pub ini_file: GSXIniFile,
+    pub diagnostics: Vec<Diagnostic>,
+}
 
-    let lines_iter = get_file_iter(path)?;
+/// Lossless counterpart of [`ParsedIniFile`].
+#[derive(Debug, Clone)]
+pub struct ParsedDocument {
+    pub document: IniDocument,
+    pub diagnostics: Vec<Diagnostic>,
+}
 
-    let mut current_section: Option<String> = None;
-    for line in lines_iter {
-        let line_string = line.as_str().trim();
-        if let Some(first_char) = line_string.chars().next() {
-            match first_char {
-                '[' => {
-                    let section_name = handle_section_line(&line, &mut ini_file);
-                    current_section = section_name;
-                }
-                token if token.is_ascii_alphabetic() => handle_key_value_line(&line, &current_section, &mut ini_file),
-                _ => {}
-            }
-        }
-    }
+pub fn parse_file(path: impl AsRef<Path>) -> io::Result<GSXIniFile> {
+    Ok(parse_file_with_diagnostics(path)?.ini_file)
+}
 
-    Ok(ini_file)
+pub fn parse_str(text: &str) -> GSXIniFile {
+    parse_document_str(text).document.to_map()
 }
 
-fn get_file_iter(path: &str) -> io::Result<impl Iterator<Item=String>> {
-    let file = File::open(path)?;
-    Ok(io::BufReader::new(file).lines().flatten())
+pub fn parse_reader(reader: impl Read) -> io::Result<GSXIniFile> {
+    Ok(parse_document_reader(reader)?.document.to_map())
 }
 
-fn handle_section_line(section_string: &String, ini_file: &mut GSXIniFile) -> Option<String> {
-    let mut handled_section_name = None;
-    let section_name_regex = Regex::new(r"^\[(?<section_name>.+)\]$").unwrap();
+pub fn parse_file_with_diagnostics(path: impl AsRef<Path>) -> io::Result<ParsedIniFile> {
+    Ok(parse_document(path)?.into())
+}
 
-    if let Some(caps) = section_name_regex.captures(&section_string) {
-        let section_name = String::from(caps["section_name"].trim());
-        if !section_name.is_empty() {
-            ini_file.insert(section_name.clone(), HashMap::new());
-            handled_section_name = Some(section_name);
-        }
-    }
-    handled_section_name
+pub fn parse_document(path: impl AsRef<Path>) -> io::Result<ParsedDocument> {
+    Ok(parse_document_bytes(&fs::read(path)?))
 }
 
-fn handle_key_value_line(key_value_string: &String, current_section_string: &Option<String>, ini_file: &mut GSXIniFile) {
-    if current_section_string.is_none() || key_value_string.find("=").is_none() { return; }
-    let current_section = current_section_string.clone().unwrap();
+pub fn parse_document_str(text: &str) -> ParsedDocument {
+    parse_text(text)
+}
 
-    let key_values: Vec<&str> = key_value_string.split("=").collect();
-    if key_values.len() != 2 { return; }
+pub fn parse_document_reader(mut reader: impl Read) -> io::Result<ParsedDocument> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    Ok(parse_document_bytes(&bytes))
+}
 
-    let key = key_values[0].trim();
-    let value = key_values[1].trim();
+/// Decodes the raw file content with its detected encoding, which the document keeps for writing.
+fn parse_document_bytes(bytes: &[u8]) -> ParsedDocument {
+    let encoding = TextEncoding::detect(bytes);
+    let mut parsed_document = parse_document_str(&encoding.decode(bytes));
+    parsed_document.document.set_encoding(encoding);
+    parsed_document
+}
 
-    if let Some(section) = ini_file.get_mut(&current_section) {
-        section.insert(String::from(key), String::from(value));
+impl From<ParsedDocument> for ParsedIniFile {
+    fn from(parsed_document: ParsedDocument) -> Self {
+        Self {
+            ini_file: parsed_document.document.to_map(),
+            diagnostics: parsed_document.diagnostics,
+        }
     }
 }
 
+/// Builds the document from the tokens of `text`, allocating each line once.
+fn parse_text(text: &str) -> ParsedDocument {
+    let mut document = IniDocument::new();
+    let mut diagnostics = vec![];
+
+    let mut current_section: Option<&str> = None;
+    // Keys per section name, so keys repeated in a duplicate section are reported as well
+    let mut section_keys: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for token in Tokenizer::new(text) {
+        let line_number = token.line_number;
+        let line_start = token.start_column();
+        let kind = match token.kind {
+            Ok(LineKind::SectionHeader { name }) => {
+                let section_name = &token.text[name.clone()];
+                if section_keys.contains_key(section_name) {
+                    diagnostics.push(Diagnostic::new(line_number, line_start, DiagnosticKind::DuplicateSection(String::from(section_name))));
+                }
+                section_keys.entry(section_name).or_default();
+                let header = Line::new(token.text, token.ending, LineKind::SectionHeader { name });
+                document.push_section(Section::new(String::from(section_name), header));
+                current_section = Some(section_name);
+                continue;
+            }
+            Ok(LineKind::KeyValue { key, value }) => {
+                if let Some(section_name) = current_section {
+                    let key_name = &token.text[key.clone()];
+                    if !section_keys.get_mut(section_name).unwrap().insert(key_name) {
+                        diagnostics.push(Diagnostic::new(line_number, line_start, DiagnosticKind::DuplicateKey(String::from(key_name))));
+                    }
+                    LineKind::KeyValue { key, value }
+                } else {
+                    diagnostics.push(Diagnostic::new(line_number, line_start, DiagnosticKind::KeyOutsideSection));
+                    LineKind::Invalid
+                }
+            }
+            Ok(kind) => kind,
+            Err((column, kind)) => {
+                // Keys below a broken header don't belong to the previous section either
+                if matches!(kind, DiagnosticKind::MalformedSectionHeader | DiagnosticKind::EmptySectionName) {
+                    current_section = None;
+                }
+                diagnostics.push(Diagnostic::new(line_number, column, kind));
+                LineKind::Invalid
+            }
+        };
+        document.push_line(Line::new(token.text, token.ending, kind));
+    }
+
+    ParsedDocument { document, diagnostics }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -74,78 +157,169 @@ mod tests {
     }
 
     #[test]
-    fn get_file_iter_file_exists() {
-        let result = get_file_iter(&get_test_file_path());
+    fn parse_document_file_exists() {
+        let result = parse_document(get_test_file_path());
         assert!(result.is_ok());
     }
 
     #[test]
-    fn get_file_iter_file_doesnt_exist() {
-        let result = get_file_iter("notexisting.ini");
+    fn parse_document_file_doesnt_exist() {
+        let result = parse_document("notexisting.ini");
         assert!(result.is_err());
     }
 
+    fn to_text(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    #[test]
+    fn all_entry_points_agree() {
+        let path = get_test_file_path();
+        let text = std::fs::read_to_string(&path).unwrap();
+
+        let from_path = parse_file(PathBuf::from(&path)).unwrap();
+        assert_eq!(parse_file(path.as_str()).unwrap(), from_path);
+        assert_eq!(parse_str(&text), from_path);
+        assert_eq!(parse_reader(text.as_bytes()).unwrap(), from_path);
+        assert_eq!(parse_document_str(&text).document, parse_document(&path).unwrap().document);
+    }
+
+    #[test]
+    fn parse_reader_decodes_windows_1252() {
+        let bytes: &[u8] = b"[general]\ncreator = M\xfcller\nafcad_path = x\n";
+        let parsed = parse_document_reader(bytes).unwrap();
+
+        assert!(parsed.diagnostics.is_empty());
+        assert_eq!(parsed.document.encoding(), TextEncoding::Windows1252);
+        assert_eq!(parsed.document.get("general", "creator"), Some("Müller"));
+
+        let mut written = vec![];
+        parsed.document.write_to(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+
     #[test]
-    fn handle_section_line_testcases() {
-        let section1_string = String::from("[section1]");
-        let section2_string = String::from("[    section2]");
-        let section3_string = String::from("[      section3       ]");
-        let section4_string = String::from("[section4       ]");
-        let section5_string = String::from("[  section 5      ]");
-        let invalid_section_string = String::from("[      ]");
-
-        let mut ini_file = GSXIniFile::new();
-
-        handle_section_line(&section1_string, &mut ini_file);
-        handle_section_line(&section2_string, &mut ini_file);
-        handle_section_line(&section3_string, &mut ini_file);
-        handle_section_line(&section4_string, &mut ini_file);
-        handle_section_line(&section5_string, &mut ini_file);
-        handle_section_line(&invalid_section_string, &mut ini_file);
-
-        assert_eq!(ini_file.keys().len(), 5);
-        assert!(ini_file.contains_key("section1"));
-        assert!(ini_file.contains_key("section2"));
-        assert!(ini_file.contains_key("section3"));
-        assert!(ini_file.contains_key("section4"));
-        assert!(ini_file.contains_key("section 5"));
+    fn parse_reader_strips_bom() {
+        let mut bytes = TextEncoding::Utf16Le { bom: true }.encode("[general]\r\ncreator = Someone\r\n");
+        let parsed = parse_document_reader(bytes.as_slice()).unwrap();
+
+        assert!(parsed.diagnostics.is_empty());
+        assert_eq!(parsed.document.sections()[0].name(), "general");
+
+        bytes = TextEncoding::Utf8Bom.encode("[general]\ncreator = Someone\n");
+        let parsed = parse_document_reader(bytes.as_slice()).unwrap();
+        assert_eq!(parsed.document.get("general", "creator"), Some("Someone"));
+
+        let mut written = vec![];
+        parsed.document.write_to(&mut written).unwrap();
+        assert_eq!(written, bytes);
     }
 
     #[test]
     fn handle_key_value_line_testcases() {
-        let current_section_string = String::from("[Testsection]");
-        let current_section = String::from("Testsection");
-        let key_value1_string = String::from("key1 = value");
-        let key_value2_string = String::from("key2= value");
-        let key_value3_string = String::from("key3 =value");
-        let key_value4_string = String::from("key4=value");
-        let key_value4_override_string = String::from("key4 = valuenew");
-        let key_value5_string = String::from("key5 = value");
-        let key_value6_string = String::from("key6 = [(value1),(value2)]");
-
-        let mut ini_file = GSXIniFile::new();
-        handle_section_line(&current_section_string, &mut ini_file);
-
-        let current_section = Some(current_section);
-
-        handle_key_value_line(&key_value1_string, &current_section, &mut ini_file);
-        handle_key_value_line(&key_value2_string, &current_section, &mut ini_file);
-        handle_key_value_line(&key_value3_string, &current_section, &mut ini_file);
-        handle_key_value_line(&key_value4_string, &current_section, &mut ini_file);
-        handle_key_value_line(&key_value4_override_string, &current_section, &mut ini_file); // Old value should be overridden
-        handle_key_value_line(&key_value5_string, &None, &mut ini_file);  // No active Section, cannot assign
-        handle_key_value_line(&key_value6_string, &current_section, &mut ini_file); // Handle arrays as value
-
-        let current_section = current_section.unwrap();
-        let section = ini_file.get(&current_section).unwrap();
+        let lines = [
+            "key5 = value", // No active Section, cannot assign
+            "[Testsection]",
+            "key1 = value",
+            "key2= value",
+            "key3 =value",
+            "key4=value",
+            "key4 = valuenew", // Old value should be overridden
+            "key6 = [(value1),(value2)]", // Handle arrays as value
+        ];
+
+        let ini_file = parse_document_str(&to_text(&lines)).document.to_map();
+        let current_section = "Testsection";
+        let section = ini_file.get(current_section).unwrap();
 
         // Assert different formatting of key value lines
-        assert!(ini_file.contains_key(&current_section));
+        assert!(ini_file.contains_key(current_section));
         assert_eq!(section.get("key1").unwrap().to_owned(), String::from("value"));
         assert_eq!(section.get("key2").unwrap().to_owned(), String::from("value"));
         assert_eq!(section.get("key3").unwrap().to_owned(), String::from("value"));
         assert_eq!(section.get("key4").unwrap().to_owned(), String::from("valuenew"));
-        assert_eq!(section.contains_key("key5"), false);
+        assert!(!section.contains_key("key5"));
         assert_eq!(section.get("key6").unwrap().to_owned(), String::from("[(value1),(value2)]"));
     }
+
+    #[test]
+    fn parse_reports_diagnostics() {
+        let lines = [
+            "orphan = 1",
+            "[general]",
+            "creator = Someone",
+            "; a comment",
+            "",
+            "_hidden = 1",
+            "broken line",
+            "pushbacklabels = a=b",
+            "creator = Someone else",
+            "[gate a 2 ",
+            "type = 9",
+        ];
+        let parsed = parse_document_str(&to_text(&lines));
+
+        let found: Vec<(usize, usize, DiagnosticKind)> = parsed
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.kind.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, 1, DiagnosticKind::KeyOutsideSection),
+                (6, 1, DiagnosticKind::InvalidKeyStart),
+                (7, 1, DiagnosticKind::MissingEquals),
+                (8, 19, DiagnosticKind::MultipleEquals),
+                (9, 1, DiagnosticKind::DuplicateKey(String::from("creator"))),
+                (10, 1, DiagnosticKind::MalformedSectionHeader),
+                (11, 1, DiagnosticKind::KeyOutsideSection),
+            ]
+        );
+        assert_eq!(parsed.diagnostics[4].severity, Severity::Warning);
+        assert_eq!(parsed.diagnostics[0].severity, Severity::Error);
+        assert_eq!(parsed.document.get("general", "creator"), Some("Someone else"));
+    }
+
+    #[test]
+    fn parse_test_file_without_errors() {
+        let parsed = parse_file_with_diagnostics(get_test_file_path()).unwrap();
+        assert!(parsed
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity != Severity::Error));
+    }
+
+    #[test]
+    fn duplicate_sections_are_merged_and_reported() {
+        let lines = [
+            "[general]",
+            "creator = Someone",
+            "deice_areas = []",
+            "[gate a 2]",
+            "type = 9",
+            "[general]",
+            "afcad_path = x",
+            "creator = Someone else",
+        ];
+        let parsed = parse_document_str(&to_text(&lines));
+
+        let found: Vec<(usize, DiagnosticKind)> = parsed
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.kind.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (6, DiagnosticKind::DuplicateSection(String::from("general"))),
+                (8, DiagnosticKind::DuplicateKey(String::from("creator"))),
+            ]
+        );
+
+        let ini_file = parsed.document.to_map();
+        assert_eq!(ini_file["general"]["creator"], "Someone else");
+        assert_eq!(ini_file["general"]["deice_areas"], "[]");
+        assert_eq!(ini_file["general"]["afcad_path"], "x");
+    }
 }
@@ -0,0 +1,259 @@
+use crate::{diff_documents, IniDocument, KeyChange};
+
+/// Which side a conflict is resolved with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    #[default]
+    Local,
+    Upstream,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeConflict {
+    /// Both sides changed `key` to different values. `None` means the key is not set on that side.
+    Key {
+        section: String,
+        key: String,
+        base: Option<String>,
+        local: Option<String>,
+        upstream: Option<String>,
+    },
+    /// The section was removed locally but changed upstream. Holds the upstream keys of the
+    /// section, so it can be restored.
+    RemovedLocally {
+        section: String,
+        upstream_entries: Vec<(String, String)>,
+    },
+    /// The section was removed upstream but changed locally.
+    RemovedUpstream { section: String },
+}
+
+/// Result of [`merge_documents`]. The document holds the local side of every conflict until it is
+/// resolved otherwise.
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    pub document: IniDocument,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeConflict {
+    pub fn section(&self) -> &str {
+        match self {
+            MergeConflict::Key { section, .. }
+            | MergeConflict::RemovedLocally { section, .. }
+            | MergeConflict::RemovedUpstream { section } => section,
+        }
+    }
+
+    fn apply_upstream(&self, document: &mut IniDocument) {
+        match self {
+            MergeConflict::Key {
+                section,
+                key,
+                upstream,
+                ..
+            } => match upstream {
+                Some(value) => set(document, section, key, value),
+                None => {
+                    document.remove(section, key);
+                }
+            },
+            MergeConflict::RemovedLocally {
+                section,
+                upstream_entries,
+            } => {
+                for (key, value) in upstream_entries {
+                    set(document, section, key, value);
+                }
+            }
+            MergeConflict::RemovedUpstream { section } => {
+                document.remove_section(section);
+            }
+        }
+    }
+}
+
+impl MergeResult {
+    /// Resolves every conflict with the resolution at the same index (local if there is none) and
+    /// returns the merged document.
+    pub fn into_document(mut self, resolutions: &[Resolution]) -> IniDocument {
+        for (index, conflict) in self.conflicts.iter().enumerate() {
+            if resolutions.get(index) == Some(&Resolution::Upstream) {
+                conflict.apply_upstream(&mut self.document);
+            }
+        }
+        self.document
+    }
+}
+
+/// Three-way merge of an upstream update into a locally edited profile. `base` is the version the
+/// local copy was made from. Changes made on only one side are taken over, changes made on both
+/// sides are conflicts unless they agree. Local formatting and comments are kept, as the merge
+/// edits the local document.
+pub fn merge_documents(base: &IniDocument, local: &IniDocument, upstream: &IniDocument) -> MergeResult {
+    let local_diff = diff_documents(base, local);
+    let upstream_diff = diff_documents(base, upstream);
+    let mut document = local.clone();
+    let mut conflicts = vec![];
+
+    for section in &upstream_diff.added_sections {
+        for (key, upstream_value) in upstream.merged_entries(section) {
+            match local.get(section, key) {
+                None => set(&mut document, section, key, upstream_value),
+                Some(local_value) if local_value == upstream_value => {}
+                Some(local_value) => conflicts.push(MergeConflict::Key {
+                    section: section.clone(),
+                    key: String::from(key),
+                    base: None,
+                    local: Some(String::from(local_value)),
+                    upstream: Some(String::from(upstream_value)),
+                }),
+            }
+        }
+    }
+
+    for section in &upstream_diff.removed_sections {
+        if local.section(section).is_none() {
+            continue;
+        }
+        if local_diff.section(section).is_some() {
+            conflicts.push(MergeConflict::RemovedUpstream {
+                section: section.clone(),
+            });
+        } else {
+            document.remove_section(section);
+        }
+    }
+
+    for section_diff in &upstream_diff.changed_sections {
+        let section = &section_diff.name;
+        if local.section(section).is_none() {
+            conflicts.push(MergeConflict::RemovedLocally {
+                section: section.clone(),
+                upstream_entries: upstream
+                    .merged_entries(section)
+                    .into_iter()
+                    .map(|(key, value)| (String::from(key), String::from(value)))
+                    .collect(),
+            });
+            continue;
+        }
+
+        for change in &section_diff.changes {
+            let key = change.key();
+            let upstream_value = match change {
+                KeyChange::Removed { .. } => None,
+                _ => upstream.get(section, key),
+            };
+            let base_value = base.get(section, key);
+            let local_value = local.get(section, key);
+            if local_value == base_value {
+                match upstream_value {
+                    Some(value) => set(&mut document, section, key, value),
+                    None => {
+                        document.remove(section, key);
+                    }
+                }
+            } else if local_value != upstream_value {
+                conflicts.push(MergeConflict::Key {
+                    section: section.clone(),
+                    key: String::from(key),
+                    base: base_value.map(String::from),
+                    local: local_value.map(String::from),
+                    upstream: upstream_value.map(String::from),
+                });
+            }
+        }
+    }
+
+    MergeResult {
+        document,
+        conflicts,
+    }
+}
+
+/// Keys and values read from a profile can always be written back, see [`IniDocument::set`].
+fn set(document: &mut IniDocument, section: &str, key: &str, value: &str) {
+    document
+        .set(section, key, value)
+        .expect("keys and values read from a profile are valid");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_document_str;
+
+    const BASE: &str = "[general]\ncreator = A\n\n[gate a 1]\ntype = 9\npushbacklabels = Left|Right\nairlinecodes = \n\n[gate a 2]\ntype = 9\n\n[gate a 3]\ntype = 9\n";
+
+    #[test]
+    fn takes_changes_from_both_sides() {
+        let base = parse_document_str(BASE).document;
+        let local = parse_document_str(
+            &BASE
+                .replace("airlinecodes = ", "airlinecodes = SWR")
+                .replace("[gate a 1]\n", "[gate a 1]\n; checked\n")
+                .replace("pushbacklabels = Left|Right", "pushbacklabels = Left|Right\nusercustomized = 1"),
+        )
+        .document;
+        let upstream = parse_document_str(
+            &BASE
+                .replace("creator = A", "creator = B")
+                .replace("[gate a 3]\ntype = 9\n", "[gate a 4]\ntype = 8\n"),
+        )
+        .document;
+
+        let result = merge_documents(&base, &local, &upstream);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            result.document.to_string(),
+            "[general]\ncreator = B\n\n[gate a 1]\n; checked\ntype = 9\npushbacklabels = Left|Right\nusercustomized = 1\nairlinecodes = SWR\n\n[gate a 2]\ntype = 9\n\n[gate a 4]\ntype = 8\n"
+        );
+    }
+
+    #[test]
+    fn reports_and_resolves_conflicts() {
+        let base = parse_document_str(BASE).document;
+        let local = parse_document_str(
+            &BASE
+                .replace("pushbacklabels = Left|Right", "pushbacklabels = Mine|Right")
+                .replace("[gate a 2]\ntype = 9\n", "[gate a 2]\ntype = 7\n")
+                .replace("[gate a 3]\ntype = 9\n", ""),
+        )
+        .document;
+        let upstream = parse_document_str(
+            &BASE
+                .replace("pushbacklabels = Left|Right", "pushbacklabels = Theirs|Right")
+                .replace("\n[gate a 2]\ntype = 9\n", "")
+                .replace("[gate a 3]\ntype = 9\n", "[gate a 3]\ntype = 8\n"),
+        )
+        .document;
+
+        let result = merge_documents(&base, &local, &upstream);
+        assert_eq!(
+            result.conflicts,
+            vec![
+                MergeConflict::RemovedUpstream {
+                    section: String::from("gate a 2")
+                },
+                MergeConflict::Key {
+                    section: String::from("gate a 1"),
+                    key: String::from("pushbacklabels"),
+                    base: Some(String::from("Left|Right")),
+                    local: Some(String::from("Mine|Right")),
+                    upstream: Some(String::from("Theirs|Right")),
+                },
+                MergeConflict::RemovedLocally {
+                    section: String::from("gate a 3"),
+                    upstream_entries: vec![(String::from("type"), String::from("8"))],
+                },
+            ]
+        );
+
+        let local_document = result.clone().into_document(&[]);
+        assert_eq!(local_document, local);
+
+        let upstream_document = result.into_document(&[Resolution::Upstream; 3]);
+        assert_eq!(upstream_document.to_map(), upstream.to_map());
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, fmt};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{de, DeiceArea, GeoPosition, ObjectPosition, PushbackAddPosition, StandId};
+
+/// Typed view of a GSX profile: the `[general]` section and every other section as a stand.
+///
+/// Deserialized with [`crate::from_document`]. Sections that appear more than once are merged
+/// into one stand the way GSX reads them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GsxProfileDocument {
+    pub general: General,
+    pub stands: Vec<Stand>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct General {
+    pub creator: Option<String>,
+    pub afcad_path: Option<String>,
+    #[serde(default, deserialize_with = "de::deice_areas")]
+    pub deice_areas: Vec<DeiceArea>,
+    /// Keys this struct doesn't have a field for.
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Stand {
+    /// Section name, e.g. `gate a 2`.
+    #[serde(skip)]
+    pub name: String,
+    /// Kept as written, the app decodes it and must be able to write back values it doesn't know.
+    #[serde(rename = "type")]
+    pub parking_type: Option<String>,
+    /// Kept as written, like `parking_type`.
+    pub pushback: Option<String>,
+    #[serde(rename = "pushbacklabels", default, deserialize_with = "de::labels")]
+    pub pushback_labels: Vec<String>,
+    /// Largest wingspan in meters GSX assigns to the stand.
+    #[serde(rename = "maxwingspan")]
+    pub max_wingspan: Option<f64>,
+    /// Clearance in meters to the left of the stand.
+    #[serde(rename = "radiusleft")]
+    pub radius_left: Option<f64>,
+    /// Clearance in meters to the right of the stand.
+    #[serde(rename = "radiusright")]
+    pub radius_right: Option<f64>,
+    /// Distance in meters from the stop position within which the jetway and gate are matched.
+    #[serde(rename = "gatedistancethreshold")]
+    pub gate_distance_threshold: Option<f64>,
+    /// Airlines preferred at the stand, e.g. `SWR` or `DLH`.
+    #[serde(rename = "airlinecodes", default, deserialize_with = "de::airline_codes")]
+    pub airline_codes: Vec<String>,
+    pub pushback_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackleftpos")]
+    pub pushback_left_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackrightpos")]
+    pub pushback_right_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackleftapproachpos")]
+    pub pushback_left_approach_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackleftapproachpos2")]
+    pub pushback_left_approach_pos2: Option<GeoPosition>,
+    #[serde(rename = "pushbackleftapproachpos3")]
+    pub pushback_left_approach_pos3: Option<GeoPosition>,
+    #[serde(rename = "pushbackrightapproachpos")]
+    pub pushback_right_approach_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackrightapproachpos2")]
+    pub pushback_right_approach_pos2: Option<GeoPosition>,
+    #[serde(rename = "pushbackrightapproachpos3")]
+    pub pushback_right_approach_pos3: Option<GeoPosition>,
+    /// End of the pushback last edited with the quick edit of the GSX menu.
+    #[serde(rename = "pushbackquickeditpos")]
+    pub pushback_quick_edit_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackquickeditapproachpos")]
+    pub pushback_quick_edit_approach_pos: Option<GeoPosition>,
+    #[serde(rename = "pushbackquickeditapproachpos2")]
+    pub pushback_quick_edit_approach_pos2: Option<GeoPosition>,
+    #[serde(rename = "pushbackquickeditapproachpos3")]
+    pub pushback_quick_edit_approach_pos3: Option<GeoPosition>,
+    #[serde(rename = "pushbackaddpos", default, deserialize_with = "de::pushback_add_positions")]
+    pub pushback_add_positions: Vec<PushbackAddPosition>,
+    /// Where the aircraft is parked. Stands without it use the parking position of the scenery.
+    pub this_parking_pos: Option<GeoPosition>,
+    pub jetway_pos: Option<GeoPosition>,
+    pub stairs_front_pos: Option<GeoPosition>,
+    pub stairs_middle_pos: Option<GeoPosition>,
+    pub stairs_rear_pos: Option<GeoPosition>,
+    pub baggage_loader_front_pos: Option<GeoPosition>,
+    pub baggage_loader_main_pos: Option<GeoPosition>,
+    pub baggage_loader_rear_pos: Option<GeoPosition>,
+    pub baggage_train_front_pos: Option<GeoPosition>,
+    pub baggage_train_main_pos: Option<GeoPosition>,
+    pub baggage_train_rear_pos: Option<GeoPosition>,
+    /// Where the nose wheel stops, as shown by the parking system.
+    pub parkingsystem_stopposition: Option<GeoPosition>,
+    /// Where the parking system (e.g. a docking display) is placed.
+    pub parkingsystem_objectposition: Option<ObjectPosition>,
+    /// Keys this struct doesn't have a field for.
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushbackKind {
+    Left,
+    Right,
+    QuickEdit,
+    /// An entry of `pushbackaddpos`.
+    Custom,
+}
+
+/// One pushback option of a stand as the tug drives it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushbackPath {
+    pub kind: PushbackKind,
+    /// From `pushbacklabels`, or the label of a custom position.
+    pub label: Option<String>,
+    /// The stand, if the profile has a position for it.
+    pub start: Option<GeoPosition>,
+    /// Approach points in the order they are driven through.
+    pub approach: Vec<GeoPosition>,
+    pub end: GeoPosition,
+}
+
+impl PushbackPath {
+    /// Start, approach points and end, in order.
+    pub fn waypoints(&self) -> Vec<GeoPosition> {
+        self.start
+            .into_iter()
+            .chain(self.approach.iter().copied())
+            .chain([self.end])
+            .collect()
+    }
+}
+
+impl Stand {
+    /// The stand id encoded in the section name, if it follows the usual naming.
+    pub fn id(&self) -> Option<StandId> {
+        self.name.parse().ok()
+    }
+
+    /// Every pushback option that has an end position: left, right, quick edit and the custom
+    /// ones, in that order.
+    pub fn pushback_paths(&self) -> Vec<PushbackPath> {
+        let start = self.pushback_pos.or(self.this_parking_pos);
+        let path = |kind, label: Option<&String>, approach: &[Option<GeoPosition>], end| PushbackPath {
+            kind,
+            label: label.cloned(),
+            start,
+            approach: approach.iter().flatten().copied().collect(),
+            end,
+        };
+
+        let mut paths = vec![];
+        if let Some(end) = self.pushback_left_pos {
+            paths.push(path(
+                PushbackKind::Left,
+                self.pushback_labels.first(),
+                &[
+                    self.pushback_left_approach_pos,
+                    self.pushback_left_approach_pos2,
+                    self.pushback_left_approach_pos3,
+                ],
+                end,
+            ));
+        }
+        if let Some(end) = self.pushback_right_pos {
+            paths.push(path(
+                PushbackKind::Right,
+                self.pushback_labels.get(1),
+                &[
+                    self.pushback_right_approach_pos,
+                    self.pushback_right_approach_pos2,
+                    self.pushback_right_approach_pos3,
+                ],
+                end,
+            ));
+        }
+        if let Some(end) = self.pushback_quick_edit_pos {
+            paths.push(path(
+                PushbackKind::QuickEdit,
+                None,
+                &[
+                    self.pushback_quick_edit_approach_pos,
+                    self.pushback_quick_edit_approach_pos2,
+                    self.pushback_quick_edit_approach_pos3,
+                ],
+                end,
+            ));
+        }
+        for add_position in &self.pushback_add_positions {
+            paths.push(PushbackPath {
+                kind: PushbackKind::Custom,
+                label: Some(add_position.label.clone()),
+                start,
+                approach: add_position.approach.clone(),
+                end: add_position.position,
+            });
+        }
+        paths
+    }
+}
+
+impl<'de> Deserialize<'de> for GsxProfileDocument {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(GsxProfileDocumentVisitor)
+    }
+}
+
+struct GsxProfileDocumentVisitor;
+
+impl<'de> Visitor<'de> for GsxProfileDocumentVisitor {
+    type Value = GsxProfileDocument;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of GSX profile sections")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut profile = GsxProfileDocument::default();
+        while let Some(section_name) = map.next_key::<String>()? {
+            if section_name == "general" {
+                profile.general = map.next_value()?;
+            } else {
+                let mut stand: Stand = map.next_value()?;
+                stand.name = section_name;
+                profile.stands.push(stand);
+            }
+        }
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_document, parse_document};
+
+    #[test]
+    fn test_file_into_profile_document() {
+        let mut test_file_path = String::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push_str("/res/test/lszh-fsdt.ini");
+        let document = parse_document(test_file_path).unwrap().document;
+        let profile: GsxProfileDocument = from_document(&document).unwrap();
+
+        assert_eq!(profile.general.creator.as_deref(), Some("Fsdreamteam"));
+        assert_eq!(profile.general.deice_areas.len(), 6);
+        assert_eq!(profile.general.deice_areas[5].label, "Rwy 16-F3");
+        assert_eq!(profile.general.deice_areas[5].heading, 334.0);
+        assert_eq!(profile.stands.len(), 125);
+
+        let stand = &profile.stands[0];
+        assert_eq!(stand.name, "gate a 2");
+        assert_eq!(stand.id().unwrap().group(), "Gate A");
+        assert!(profile.stands.iter().all(|stand| stand.id().is_some()));
+        assert_eq!(stand.parking_type.as_deref(), Some("9"));
+        assert_eq!(stand.pushback.as_deref(), Some("2"));
+        assert_eq!(
+            stand.pushback_labels,
+            vec!["Nose Right/Tail Left (LEFT)", "Facing W on Taxi N"]
+        );
+        assert_eq!(stand.pushback_left_pos, None);
+        assert!(stand.pushback_right_pos.is_some());
+        assert_eq!(stand.extra["walkertype"], "GsxJetwayPassenger");
+        assert!(!stand.extra.contains_key("pushbacklabels"));
+        assert_eq!(stand.max_wingspan, Some(50.0));
+        assert_eq!(stand.radius_left, Some(25.0));
+        assert_eq!(stand.gate_distance_threshold, Some(25.0));
+        assert!(stand.airline_codes.is_empty());
+        assert!(!stand.extra.contains_key("maxwingspan"));
+        assert_eq!(stand.this_parking_pos.unwrap().heading, Some(6.01692008972168));
+        assert_eq!(stand.parkingsystem_stopposition.unwrap().heading, None);
+        assert_eq!(stand.parkingsystem_objectposition.unwrap().height, 1.18);
+        assert!(stand.stairs_front_pos.is_none());
+        assert!(stand.extra.keys().all(|key| !key.ends_with("_pos")));
+
+        let paths = stand.pushback_paths();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].kind, PushbackKind::Right);
+        assert_eq!(paths[0].label.as_deref(), Some("Facing W on Taxi N"));
+        assert_eq!(paths[0].start, stand.pushback_pos);
+        assert_eq!(paths[0].approach.len(), 2);
+        assert_eq!(paths[0].end.heading, Some(-83.98307991));
+        assert_eq!(paths[0].waypoints().len(), 4);
+
+        let stand = profile.stands.iter().find(|stand| stand.name == "gate a 8").unwrap();
+        let paths = stand.pushback_paths();
+        assert_eq!(paths[1].kind, PushbackKind::QuickEdit);
+        assert_eq!(paths[1].approach.len(), 2);
+
+        let stand = profile.stands.iter().find(|stand| stand.name == "gate e 54").unwrap();
+        let paths = stand.pushback_paths();
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[2].kind, PushbackKind::Custom);
+        assert_eq!(paths[2].label.as_deref(), Some("LONG - Facing SE on Taxi F"));
+        assert_eq!(paths[2].waypoints().len(), 5);
+
+        let stand = profile
+            .stands
+            .iter()
+            .find(|stand| stand.name == "e parking 300")
+            .unwrap();
+        assert!(stand.pushback_pos.is_none());
+        assert!(stand.this_parking_pos.is_none());
+        assert!(stand.pushback_paths().is_empty());
+    }
+
+    #[test]
+    fn codes_are_kept_as_written() {
+        let document = crate::parse_document_str("[gate a 1]\ntype = 09\npushback = left\n").document;
+        let stand: Stand = crate::from_section(&document, "gate a 1").unwrap();
+        assert_eq!(stand.parking_type.as_deref(), Some("09"));
+        assert_eq!(stand.pushback.as_deref(), Some("left"));
+    }
+}
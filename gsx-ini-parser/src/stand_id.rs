@@ -0,0 +1,156 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::format::natural_cmp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StandKind {
+    Gate,
+    Parking,
+}
+
+/// A stand identifier parsed from a section name like `gate a 2`, `sw parking 101` or `parking 7`.
+///
+/// Ordered by kind, area and number, so `gate a 2` comes before `gate a 10` and stands of the same
+/// area end up next to each other.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StandId {
+    pub kind: StandKind,
+    /// Apron or terminal prefix in lowercase, e.g. `a` or `sw`.
+    pub area: Option<String>,
+    pub number: u32,
+    /// Letters after the number, e.g. `l` in `gate a 10l`.
+    pub suffix: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandIdError;
+
+impl fmt::Display for StandIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not a gate or parking name")
+    }
+}
+
+impl std::error::Error for StandIdError {}
+
+impl StandId {
+    /// Name of the area the stand belongs to, e.g. `Gate A` or `SW Parking`.
+    pub fn group(&self) -> String {
+        match (self.kind, &self.area) {
+            (StandKind::Gate, Some(area)) => format!("Gate {}", area.to_uppercase()),
+            (StandKind::Gate, None) => String::from("Gate"),
+            (StandKind::Parking, Some(area)) => format!("{} Parking", area.to_uppercase()),
+            (StandKind::Parking, None) => String::from("Parking"),
+        }
+    }
+}
+
+impl FromStr for StandId {
+    type Err = StandIdError;
+
+    /// Accepts `gate <area> <number>`, `<area> parking <number>` and both without the area.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let lowercase_name = name.to_lowercase();
+        let tokens: Vec<&str> = lowercase_name.split_whitespace().collect();
+        let (kind, area, number) = match tokens[..] {
+            ["gate", area, number] => (StandKind::Gate, Some(area), number),
+            ["gate", number] => (StandKind::Gate, None, number),
+            [area, "parking", number] => (StandKind::Parking, Some(area), number),
+            ["parking", number] => (StandKind::Parking, None, number),
+            _ => return Err(StandIdError),
+        };
+
+        let digits_end = number
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(number.len());
+        let (digits, suffix) = number.split_at(digits_end);
+        if !suffix.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(StandIdError);
+        }
+        Ok(StandId {
+            kind,
+            area: area.map(String::from),
+            number: digits.parse().map_err(|_| StandIdError)?,
+            suffix: String::from(suffix),
+        })
+    }
+}
+
+impl fmt::Display for StandId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, &self.area) {
+            (StandKind::Gate, Some(area)) => write!(f, "gate {} ", area)?,
+            (StandKind::Gate, None) => f.write_str("gate ")?,
+            (StandKind::Parking, Some(area)) => write!(f, "{} parking ", area)?,
+            (StandKind::Parking, None) => f.write_str("parking ")?,
+        }
+        write!(f, "{}{}", self.number, self.suffix)
+    }
+}
+
+/// Orders section names by their [`StandId`]. Names that are not a stand id come after the ones
+/// that are, in natural order.
+pub fn compare_stand_names(a: &str, b: &str) -> Ordering {
+    match (a.parse::<StandId>(), b.parse::<StandId>()) {
+        (Ok(a_id), Ok(b_id)) => a_id.cmp(&b_id).then_with(|| natural_cmp(a, b)),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => natural_cmp(a, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_section_names() {
+        let id: StandId = "gate a 2".parse().unwrap();
+        assert_eq!(id.kind, StandKind::Gate);
+        assert_eq!(id.area.as_deref(), Some("a"));
+        assert_eq!(id.number, 2);
+        assert_eq!(id.group(), "Gate A");
+
+        let id: StandId = "SW Parking 101".parse().unwrap();
+        assert_eq!(id.kind, StandKind::Parking);
+        assert_eq!(id.area.as_deref(), Some("sw"));
+        assert_eq!(id.number, 101);
+        assert_eq!(id.group(), "SW Parking");
+        assert_eq!(id.to_string(), "sw parking 101");
+
+        let id: StandId = "gate 10l".parse().unwrap();
+        assert_eq!(id.area, None);
+        assert_eq!(id.suffix, "l");
+        assert_eq!(id.to_string(), "gate 10l");
+
+        assert!("general".parse::<StandId>().is_err());
+        assert!("gate a".parse::<StandId>().is_err());
+        assert!("gate a 1-2".parse::<StandId>().is_err());
+    }
+
+    #[test]
+    fn sorts_by_area_and_number() {
+        let mut names = vec![
+            "sw parking 21",
+            "gate a 10",
+            "remote 1",
+            "e parking 300",
+            "gate b 1",
+            "gate a 2",
+            "sw parking 101",
+        ];
+        names.sort_by(|a, b| compare_stand_names(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "gate a 2",
+                "gate a 10",
+                "gate b 1",
+                "e parking 300",
+                "sw parking 21",
+                "sw parking 101",
+                "remote 1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::ops::Range;
+
+use crate::{DiagnosticKind, LineEnding, LineKind};
+
+/// Splits a profile into lines and classifies each of them in a single pass, without copying.
+///
+/// Every [`Token`] borrows from the text it was created with, so callers that only need a few keys
+/// (e.g. the creator of every installed profile) don't pay for building an [`crate::IniDocument`].
+/// Whether a key is inside a section is not checked here, as that depends on the lines before it.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    remaining: &'a str,
+    line_number: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// 1-based line number.
+    pub line_number: usize,
+    /// The line without its line ending.
+    pub text: &'a str,
+    pub ending: LineEnding,
+    /// What the line is, or the 1-based column and kind of the problem found on it.
+    pub kind: Result<LineKind, (usize, DiagnosticKind)>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            remaining: text,
+            line_number: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let raw_line_end = self
+            .remaining
+            .find('\n')
+            .map_or(self.remaining.len(), |index| index + 1);
+        let (raw_line, remaining) = self.remaining.split_at(raw_line_end);
+        self.remaining = remaining;
+        self.line_number += 1;
+
+        let (text, ending) = LineEnding::split(raw_line);
+        Some(Token {
+            line_number: self.line_number,
+            text,
+            ending,
+            kind: classify_line(text),
+        })
+    }
+}
+
+impl<'a> Token<'a> {
+    pub fn section_name(&self) -> Option<&'a str> {
+        match &self.kind {
+            Ok(LineKind::SectionHeader { name }) => Some(&self.text[name.clone()]),
+            _ => None,
+        }
+    }
+
+    pub fn key(&self) -> Option<&'a str> {
+        match &self.kind {
+            Ok(LineKind::KeyValue { key, .. }) => Some(&self.text[key.clone()]),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<&'a str> {
+        match &self.kind {
+            Ok(LineKind::KeyValue { value, .. }) => Some(&self.text[value.clone()]),
+            _ => None,
+        }
+    }
+
+    /// 1-based column of the first non-whitespace character.
+    pub fn start_column(&self) -> usize {
+        column_of(self.text, self.text.len() - self.text.trim_start().len())
+    }
+}
+
+fn classify_line(line: &str) -> Result<LineKind, (usize, DiagnosticKind)> {
+    let line_start = line.len() - line.trim_start().len();
+    match line[line_start..].chars().next() {
+        None => Ok(LineKind::Blank),
+        Some('[') => handle_section_line(line)
+            .map(|name| LineKind::SectionHeader { name })
+            .map_err(|kind| (column_of(line, line_start), kind)),
+        Some(';' | '#') => Ok(LineKind::Comment),
+        Some(token) if token.is_ascii_alphabetic() => handle_key_value_line(line),
+        Some(_) => Err((column_of(line, line_start), DiagnosticKind::InvalidKeyStart)),
+    }
+}
+
+/// Returns the byte range of the section name within the header line.
+fn handle_section_line(section_string: &str) -> Result<Range<usize>, DiagnosticKind> {
+    let Some(untrimmed_name) = section_string
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .filter(|name| !name.is_empty())
+    else {
+        return Err(DiagnosticKind::MalformedSectionHeader);
+    };
+    let section_name = untrimmed_name.trim();
+    if section_name.is_empty() {
+        return Err(DiagnosticKind::EmptySectionName);
+    }
+    let name_start = 1 + (untrimmed_name.len() - untrimmed_name.trim_start().len());
+    Ok(name_start..name_start + section_name.len())
+}
+
+/// Returns the key and value ranges of the line, or the column and kind of the problem found on it.
+fn handle_key_value_line(key_value_string: &str) -> Result<LineKind, (usize, DiagnosticKind)> {
+    let key_start = key_value_string.len() - key_value_string.trim_start().len();
+    let Some(equals_index) = key_value_string.find('=') else {
+        return Err((column_of(key_value_string, key_start), DiagnosticKind::MissingEquals));
+    };
+
+    let value_part = &key_value_string[equals_index + 1..];
+    if let Some(second_equals_offset) = value_part.find('=') {
+        let second_equals_index = equals_index + 1 + second_equals_offset;
+        return Err((column_of(key_value_string, second_equals_index), DiagnosticKind::MultipleEquals));
+    }
+
+    let key = key_value_string[..equals_index].trim();
+    let value = value_part.trim();
+    let value_start = equals_index + 1 + (value_part.len() - value_part.trim_start().len());
+
+    Ok(LineKind::KeyValue {
+        key: key_start..key_start + key.len(),
+        value: value_start..value_start + value.len(),
+    })
+}
+
+#[inline]
+fn column_of(line: &str, byte_index: usize) -> usize {
+    line[..byte_index].chars().count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_section_line_testcases() {
+        let section1_string = String::from("[section1]");
+        let section2_string = String::from("[    section2]");
+        let section3_string = String::from("[      section3       ]");
+        let section4_string = String::from("[section4       ]");
+        let section5_string = String::from("[  section 5      ]");
+        let invalid_section_string = String::from("[      ]");
+
+        let section_name = |section_string: &String| {
+            String::from(&section_string[handle_section_line(section_string).unwrap()])
+        };
+
+        assert_eq!(section_name(&section1_string), "section1");
+        assert_eq!(section_name(&section2_string), "section2");
+        assert_eq!(section_name(&section3_string), "section3");
+        assert_eq!(section_name(&section4_string), "section4");
+        assert_eq!(section_name(&section5_string), "section 5");
+        assert_eq!(
+            handle_section_line(&invalid_section_string),
+            Err(DiagnosticKind::EmptySectionName)
+        );
+        assert_eq!(handle_section_line("[]"), Err(DiagnosticKind::MalformedSectionHeader));
+        assert_eq!(handle_section_line("[gate a 2 "), Err(DiagnosticKind::MalformedSectionHeader));
+        assert_eq!(handle_section_line(" [gate a 2]"), Err(DiagnosticKind::MalformedSectionHeader));
+    }
+
+    #[test]
+    fn tokens_borrow_from_the_text() {
+        let text = "[general]\r\ncreator = Someone\r\n\r\n; comment\r\nbroken";
+        let tokens: Vec<Token> = Tokenizer::new(text).collect();
+
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].section_name(), Some("general"));
+        assert_eq!(tokens[1].key(), Some("creator"));
+        assert_eq!(tokens[1].value(), Some("Someone"));
+        assert_eq!(tokens[1].ending, LineEnding::CrLf);
+        assert_eq!(tokens[2].kind, Ok(LineKind::Blank));
+        assert_eq!(tokens[3].kind, Ok(LineKind::Comment));
+        assert_eq!(tokens[4].line_number, 5);
+        assert_eq!(tokens[4].ending, LineEnding::None);
+        assert_eq!(tokens[4].kind, Err((1, DiagnosticKind::MissingEquals)));
+
+        let text_range = text.as_bytes().as_ptr_range();
+        let value = tokens[1].value().unwrap();
+        assert!(text_range.contains(&value.as_ptr()));
+    }
+}
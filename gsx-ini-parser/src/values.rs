@@ -0,0 +1,877 @@
+use std::{error::Error, fmt, iter::Peekable, ops::Range, str::Chars};
+
+use crate::Section;
+
+/// `lat lon heading` as GSX writes it for every service position. A few keys, like
+/// `parkingsystem_stopposition`, only have `lat lon`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub heading: Option<f64>,
+}
+
+/// `lat lon heading height` of `parkingsystem_objectposition`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub heading: f64,
+    pub height: f64,
+}
+
+/// One entry of `deice_areas`: `(lat, lon, heading, size, "label", "object type")`. Older profiles
+/// don't have the object type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeiceArea {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub heading: f64,
+    /// Length of a side of the square pad in metres.
+    pub size: f64,
+    pub label: String,
+    pub object_type: Option<String>,
+}
+
+/// One entry of `pushbackaddpos`, a pushback position the user added in GSX.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushbackAddPosition {
+    pub label: String,
+    pub snap: bool,
+    /// Approach points in the order they are driven through.
+    pub approach: Vec<GeoPosition>,
+    /// Where the pushback ends.
+    pub position: GeoPosition,
+}
+
+/// A single item of a bracketed tuple list such as `deice_areas`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TupleValue {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueErrorKind {
+    /// The value has a different number of parts than the shape requires.
+    WrongCount { expected: &'static str, found: usize },
+    InvalidNumber(String),
+    OutOfRange(String),
+    /// A flag that is neither `0` nor `1`.
+    InvalidBool,
+    /// Unexpected character at the given character offset of a tuple list.
+    UnexpectedCharacter { offset: usize, found: Option<char> },
+    UnterminatedString,
+    /// An entry of a list like `pushbackaddpos` is missing a field or has one of the wrong type.
+    InvalidField(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+    pub value: String,
+    pub kind: ValueErrorKind,
+}
+
+impl ValueError {
+    fn new(value: &str, kind: ValueErrorKind) -> Self {
+        Self {
+            value: String::from(value),
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for ValueErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueErrorKind::WrongCount { expected, found } => {
+                write!(f, "expected {} values, found {}", expected, found)
+            }
+            ValueErrorKind::InvalidNumber(token) => write!(f, "'{}' is not a number", token),
+            ValueErrorKind::OutOfRange(token) => write!(f, "'{}' is out of range", token),
+            ValueErrorKind::InvalidBool => write!(f, "expected 0 or 1"),
+            ValueErrorKind::UnexpectedCharacter {
+                offset,
+                found: Some(found),
+            } => write!(f, "unexpected '{}' at offset {}", found, offset),
+            ValueErrorKind::UnexpectedCharacter { offset, found: None } => {
+                write!(f, "unexpected end at offset {}", offset)
+            }
+            ValueErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ValueErrorKind::InvalidField(field) => write!(f, "missing or invalid '{}'", field),
+        }
+    }
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value '{}': {}", self.value, self.kind)
+    }
+}
+
+impl Error for ValueError {}
+
+impl TupleValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            TupleValue::Number(number) => Some(*number),
+            TupleValue::Text(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TupleValue::Number(_) => None,
+            TupleValue::Text(text) => Some(text),
+        }
+    }
+}
+
+pub fn parse_position(value: &str) -> Result<GeoPosition, ValueError> {
+    let numbers = parse_numbers(value, "2 or 3", |count| count == 2 || count == 3)?;
+    Ok(GeoPosition {
+        latitude: check_latitude(value, numbers[0])?,
+        longitude: check_longitude(value, numbers[1])?,
+        heading: numbers.get(2).copied(),
+    })
+}
+
+pub fn parse_object_position(value: &str) -> Result<ObjectPosition, ValueError> {
+    let numbers = parse_numbers(value, "4", |count| count == 4)?;
+    Ok(ObjectPosition {
+        latitude: check_latitude(value, numbers[0])?,
+        longitude: check_longitude(value, numbers[1])?,
+        heading: numbers[2],
+        height: numbers[3],
+    })
+}
+
+/// Whether the value of `key` holds positions, like `pushback_pos`, `parkingsystem_objectposition`
+/// or the list of `pushbackaddpos`. `snapleftpushbackpos` and `snaprightpushbackpos` are flags.
+pub fn is_position_key(key: &str) -> bool {
+    key.contains("pos") && !key.starts_with("snap")
+}
+
+/// Latitude and longitude of a position value like `pushback_pos` or
+/// `parkingsystem_objectposition`. Values of other keys are never positions, even if they look like
+/// one (`loadertype = 3 3 2`).
+pub(crate) fn coordinates_of(key: &str, value: &str) -> Option<(f64, f64)> {
+    if !is_position_key(key) {
+        return None;
+    }
+    match parse_position(value) {
+        Ok(position) => Some((position.latitude, position.longitude)),
+        Err(_) => parse_object_position(value)
+            .ok()
+            .map(|position| (position.latitude, position.longitude)),
+    }
+}
+
+/// Byte ranges of the latitudes and longitudes in a `deice_areas` or `pushbackaddpos` list, in the
+/// order they appear. `None` for other keys and for lists the decoders don't accept.
+pub(crate) fn list_coordinate_spans(key: &str, value: &str) -> Option<Vec<Range<usize>>> {
+    match key {
+        "deice_areas" => {
+            parse_deice_areas(value).ok()?;
+        }
+        "pushbackaddpos" => {
+            parse_pushback_add_positions(value).ok()?;
+        }
+        _ => return None,
+    }
+    let mut chars = value.chars().peekable();
+    let PythonValue::Sequence(entries) = parse_python_value(value, &mut chars, &mut 0).ok()? else {
+        return None;
+    };
+
+    // Latitude and longitude are the first two numbers of every position tuple
+    let lat_lon = |item: &PythonValue| match item {
+        PythonValue::Sequence(items) => items
+            .iter()
+            .take(2)
+            .filter_map(|item| match item {
+                PythonValue::Number(_, span) => Some(span.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+    let spans: Vec<Range<usize>> = entries
+        .iter()
+        .flat_map(|entry| match entry {
+            PythonValue::Dict(fields) => fields
+                .iter()
+                .flat_map(|(name, field)| match (name.as_str(), field) {
+                    ("pos", _) => lat_lon(field),
+                    ("approach", PythonValue::Sequence(items)) => items.iter().flat_map(lat_lon).collect(),
+                    _ => vec![],
+                })
+                .collect(),
+            _ => lat_lon(entry),
+        })
+        .collect();
+
+    // The parser counts characters, labels may have some that take more than a byte
+    let byte_offsets: Vec<usize> = value
+        .char_indices()
+        .map(|(byte_offset, _)| byte_offset)
+        .chain([value.len()])
+        .collect();
+    Some(
+        spans
+            .into_iter()
+            .map(|span| byte_offsets[span.start]..byte_offsets[span.end])
+            .collect(),
+    )
+}
+
+/// Splits a `|` separated list like `pushbacklabels`. An empty value has no labels.
+pub fn parse_labels(value: &str) -> Vec<String> {
+    if value.trim().is_empty() {
+        return vec![];
+    }
+    value.split('|').map(String::from).collect()
+}
+
+/// Splits a list of airline codes like `airlinecodes`, separated by spaces or commas.
+pub fn parse_airline_codes(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|code| !code.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parses a space separated list of integers like `loadertype`.
+pub fn parse_int_list(value: &str) -> Result<Vec<i64>, ValueError> {
+    value
+        .split_whitespace()
+        .map(|token| {
+            token.parse::<i64>().map_err(|_| {
+                ValueError::new(value, ValueErrorKind::InvalidNumber(String::from(token)))
+            })
+        })
+        .collect()
+}
+
+pub fn parse_bool(value: &str) -> Result<bool, ValueError> {
+    match value.trim() {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(ValueError::new(value, ValueErrorKind::InvalidBool)),
+    }
+}
+
+/// Parses a Python style list of tuples like `deice_areas` or `pushbackaddpos`:
+/// `[(47.46, 8.56, 96, 20, "Rwy 10/28/32-C1","VgdsDeIceWall"),(...)]`.
+pub fn parse_tuple_list(value: &str) -> Result<Vec<Vec<TupleValue>>, ValueError> {
+    let mut tuples = vec![];
+    let mut chars = value.chars().peekable();
+    let mut offset = 0;
+
+    expect(value, &mut chars, &mut offset, '[')?;
+    skip_whitespace(&mut chars, &mut offset);
+    if chars.peek() == Some(&']') {
+        next(&mut chars, &mut offset);
+    } else {
+        loop {
+            tuples.push(parse_tuple(value, &mut chars, &mut offset)?);
+            skip_whitespace(&mut chars, &mut offset);
+            match next(&mut chars, &mut offset) {
+                Some(',') => continue,
+                Some(']') => break,
+                found => return Err(unexpected(value, offset - 1, found)),
+            }
+        }
+    }
+    skip_whitespace(&mut chars, &mut offset);
+    if let Some(found) = chars.next() {
+        return Err(unexpected(value, offset, Some(found)));
+    }
+    Ok(tuples)
+}
+
+/// Parses the `deice_areas` list of `[general]`.
+pub fn parse_deice_areas(value: &str) -> Result<Vec<DeiceArea>, ValueError> {
+    parse_tuple_list(value)?
+        .iter()
+        .map(|tuple| {
+            if tuple.len() != 5 && tuple.len() != 6 {
+                return Err(ValueError::new(
+                    value,
+                    ValueErrorKind::WrongCount {
+                        expected: "5 or 6",
+                        found: tuple.len(),
+                    },
+                ));
+            }
+            let number = |index: usize| match &tuple[index] {
+                TupleValue::Number(number) => Ok(*number),
+                TupleValue::Text(text) => Err(ValueError::new(
+                    value,
+                    ValueErrorKind::InvalidNumber(text.clone()),
+                )),
+            };
+            let text = |item: &TupleValue| match item {
+                TupleValue::Number(number) => number.to_string(),
+                TupleValue::Text(text) => text.clone(),
+            };
+            Ok(DeiceArea {
+                latitude: check_latitude(value, number(0)?)?,
+                longitude: check_longitude(value, number(1)?)?,
+                heading: number(2)?,
+                size: number(3)?,
+                label: text(&tuple[4]),
+                object_type: tuple.get(5).map(text),
+            })
+        })
+        .collect()
+}
+
+/// Parses the `pushbackaddpos` list, which GSX writes as a Python list of dicts:
+/// `[{'snap': False, 'approach': [(lat, lon, hdg), None], 'pos': (lat, lon, hdg), 'label': u'...'}]`.
+/// Unused approach slots are `None` and skipped.
+pub fn parse_pushback_add_positions(value: &str) -> Result<Vec<PushbackAddPosition>, ValueError> {
+    let mut chars = value.chars().peekable();
+    let mut offset = 0;
+    let parsed = parse_python_value(value, &mut chars, &mut offset)?;
+    skip_whitespace(&mut chars, &mut offset);
+    if let Some(found) = chars.next() {
+        return Err(unexpected(value, offset, Some(found)));
+    }
+
+    let invalid = |field| ValueError::new(value, ValueErrorKind::InvalidField(field));
+    let to_position = |item: &PythonValue, field| match item {
+        PythonValue::Sequence(items) if items.len() == 2 || items.len() == 3 => {
+            let mut numbers = items.iter().map(|item| match item {
+                PythonValue::Number(number, _) => Ok(*number),
+                _ => Err(invalid(field)),
+            });
+            Ok(GeoPosition {
+                latitude: check_latitude(value, numbers.next().unwrap()?)?,
+                longitude: check_longitude(value, numbers.next().unwrap()?)?,
+                heading: numbers.next().transpose()?,
+            })
+        }
+        _ => Err(invalid(field)),
+    };
+
+    let PythonValue::Sequence(entries) = parsed else {
+        return Err(invalid("list"));
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            let PythonValue::Dict(fields) = entry else {
+                return Err(invalid("dict"));
+            };
+            let field = |name| {
+                fields
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, field_value)| field_value)
+            };
+            let label = match field("label") {
+                Some(PythonValue::Text(label)) => label.clone(),
+                None => String::new(),
+                Some(_) => return Err(invalid("label")),
+            };
+            let snap = match field("snap") {
+                Some(PythonValue::Bool(snap)) => *snap,
+                None => false,
+                Some(_) => return Err(invalid("snap")),
+            };
+            let approach = match field("approach") {
+                Some(PythonValue::Sequence(items)) => items
+                    .iter()
+                    .filter(|item| **item != PythonValue::None)
+                    .map(|item| to_position(item, "approach"))
+                    .collect::<Result<Vec<GeoPosition>, ValueError>>()?,
+                None => vec![],
+                Some(_) => return Err(invalid("approach")),
+            };
+            let position = to_position(field("pos").ok_or_else(|| invalid("pos"))?, "pos")?;
+            Ok(PushbackAddPosition {
+                label,
+                snap,
+                approach,
+                position,
+            })
+        })
+        .collect()
+}
+
+impl Section {
+    pub fn get_position(&self, key: &str) -> Option<Result<GeoPosition, ValueError>> {
+        self.get(key).map(parse_position)
+    }
+
+    pub fn get_object_position(&self, key: &str) -> Option<Result<ObjectPosition, ValueError>> {
+        self.get(key).map(parse_object_position)
+    }
+
+    pub fn get_labels(&self, key: &str) -> Option<Vec<String>> {
+        self.get(key).map(parse_labels)
+    }
+
+    pub fn get_int_list(&self, key: &str) -> Option<Result<Vec<i64>, ValueError>> {
+        self.get(key).map(parse_int_list)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<Result<bool, ValueError>> {
+        self.get(key).map(parse_bool)
+    }
+
+    pub fn get_tuple_list(&self, key: &str) -> Option<Result<Vec<Vec<TupleValue>>, ValueError>> {
+        self.get(key).map(parse_tuple_list)
+    }
+}
+
+fn parse_numbers(
+    value: &str,
+    expected: &'static str,
+    count_is_valid: impl Fn(usize) -> bool,
+) -> Result<Vec<f64>, ValueError> {
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    if !count_is_valid(tokens.len()) {
+        return Err(ValueError::new(
+            value,
+            ValueErrorKind::WrongCount {
+                expected,
+                found: tokens.len(),
+            },
+        ));
+    }
+    tokens
+        .iter()
+        .map(|token| parse_number(value, token))
+        .collect()
+}
+
+fn parse_number(value: &str, token: &str) -> Result<f64, ValueError> {
+    match token.parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(ValueError::new(
+            value,
+            ValueErrorKind::InvalidNumber(String::from(token)),
+        )),
+    }
+}
+
+fn check_latitude(value: &str, latitude: f64) -> Result<f64, ValueError> {
+    if (-90.0..=90.0).contains(&latitude) {
+        Ok(latitude)
+    } else {
+        Err(ValueError::new(
+            value,
+            ValueErrorKind::OutOfRange(latitude.to_string()),
+        ))
+    }
+}
+
+fn check_longitude(value: &str, longitude: f64) -> Result<f64, ValueError> {
+    if (-180.0..=180.0).contains(&longitude) {
+        Ok(longitude)
+    } else {
+        Err(ValueError::new(
+            value,
+            ValueErrorKind::OutOfRange(longitude.to_string()),
+        ))
+    }
+}
+
+fn parse_tuple(
+    value: &str,
+    chars: &mut Peekable<Chars>,
+    offset: &mut usize,
+) -> Result<Vec<TupleValue>, ValueError> {
+    let mut items = vec![];
+    skip_whitespace(chars, offset);
+    expect(value, chars, offset, '(')?;
+    skip_whitespace(chars, offset);
+    if chars.peek() == Some(&')') {
+        next(chars, offset);
+        return Ok(items);
+    }
+    loop {
+        skip_whitespace(chars, offset);
+        match chars.peek() {
+            Some(&quote) if quote == '"' || quote == '\'' => {
+                next(chars, offset);
+                items.push(TupleValue::Text(parse_string(value, chars, offset, quote)?));
+            }
+            _ => {
+                let start = *offset;
+                let mut token = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ',' || c == ')' {
+                        break;
+                    }
+                    token.push(c);
+                    next(chars, offset);
+                }
+                let token = token.trim();
+                if token.is_empty() {
+                    return Err(unexpected(value, start, chars.peek().copied()));
+                }
+                items.push(TupleValue::Number(parse_number(value, token)?));
+            }
+        }
+        skip_whitespace(chars, offset);
+        match next(chars, offset) {
+            Some(',') => continue,
+            Some(')') => return Ok(items),
+            found => return Err(unexpected(value, *offset - 1, found)),
+        }
+    }
+}
+
+/// The subset of Python literals GSX writes into profiles.
+#[derive(Debug, Clone, PartialEq)]
+enum PythonValue {
+    /// A number with the character range it was written at.
+    Number(f64, Range<usize>),
+    Text(String),
+    Bool(bool),
+    None,
+    /// A list or a tuple.
+    Sequence(Vec<PythonValue>),
+    Dict(Vec<(String, PythonValue)>),
+}
+
+fn parse_python_value(
+    value: &str,
+    chars: &mut Peekable<Chars>,
+    offset: &mut usize,
+) -> Result<PythonValue, ValueError> {
+    skip_whitespace(chars, offset);
+    match chars.peek().copied() {
+        Some(open @ ('[' | '(')) => {
+            next(chars, offset);
+            let close = if open == '[' { ']' } else { ')' };
+            let mut items = vec![];
+            loop {
+                skip_whitespace(chars, offset);
+                if chars.peek() == Some(&close) {
+                    next(chars, offset);
+                    return Ok(PythonValue::Sequence(items));
+                }
+                items.push(parse_python_value(value, chars, offset)?);
+                skip_whitespace(chars, offset);
+                match next(chars, offset) {
+                    Some(',') => continue,
+                    Some(c) if c == close => return Ok(PythonValue::Sequence(items)),
+                    found => return Err(unexpected(value, *offset - 1, found)),
+                }
+            }
+        }
+        Some('{') => {
+            next(chars, offset);
+            let mut fields = vec![];
+            loop {
+                skip_whitespace(chars, offset);
+                if chars.peek() == Some(&'}') {
+                    next(chars, offset);
+                    return Ok(PythonValue::Dict(fields));
+                }
+                let key_start = *offset;
+                let PythonValue::Text(key) = parse_python_value(value, chars, offset)? else {
+                    return Err(unexpected(value, key_start, value.chars().nth(key_start)));
+                };
+                expect(value, chars, offset, ':')?;
+                fields.push((key, parse_python_value(value, chars, offset)?));
+                skip_whitespace(chars, offset);
+                match next(chars, offset) {
+                    Some(',') => continue,
+                    Some('}') => return Ok(PythonValue::Dict(fields)),
+                    found => return Err(unexpected(value, *offset - 1, found)),
+                }
+            }
+        }
+        Some(quote @ ('"' | '\'')) => {
+            next(chars, offset);
+            Ok(PythonValue::Text(parse_string(value, chars, offset, quote)?))
+        }
+        _ => {
+            let start = *offset;
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if matches!(c, ',' | ':' | ')' | ']' | '}' | '"' | '\'') || c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                next(chars, offset);
+            }
+            match token.as_str() {
+                "" => Err(unexpected(value, start, chars.peek().copied())),
+                "True" => Ok(PythonValue::Bool(true)),
+                "False" => Ok(PythonValue::Bool(false)),
+                "None" => Ok(PythonValue::None),
+                // String prefixes like u'...'
+                "u" | "r" | "b" if matches!(chars.peek(), Some('"' | '\'')) => {
+                    parse_python_value(value, chars, offset)
+                }
+                _ => Ok(PythonValue::Number(parse_number(value, &token)?, start..*offset)),
+            }
+        }
+    }
+}
+
+fn parse_string(
+    value: &str,
+    chars: &mut Peekable<Chars>,
+    offset: &mut usize,
+    quote: char,
+) -> Result<String, ValueError> {
+    let mut text = String::new();
+    loop {
+        match next(chars, offset) {
+            Some('\\') => match next(chars, offset) {
+                Some(escaped) => text.push(escaped),
+                None => return Err(ValueError::new(value, ValueErrorKind::UnterminatedString)),
+            },
+            Some(c) if c == quote => return Ok(text),
+            Some(c) => text.push(c),
+            None => return Err(ValueError::new(value, ValueErrorKind::UnterminatedString)),
+        }
+    }
+}
+
+fn next(chars: &mut Peekable<Chars>, offset: &mut usize) -> Option<char> {
+    // Offset moves past the end as well, so errors for a missing character point behind the value
+    *offset += 1;
+    chars.next()
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>, offset: &mut usize) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        next(chars, offset);
+    }
+}
+
+fn expect(
+    value: &str,
+    chars: &mut Peekable<Chars>,
+    offset: &mut usize,
+    expected: char,
+) -> Result<(), ValueError> {
+    skip_whitespace(chars, offset);
+    match next(chars, offset) {
+        Some(c) if c == expected => Ok(()),
+        found => Err(unexpected(value, *offset - 1, found)),
+    }
+}
+
+fn unexpected(value: &str, offset: usize, found: Option<char>) -> ValueError {
+    ValueError::new(value, ValueErrorKind::UnexpectedCharacter { offset, found })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        assert_eq!(
+            parse_position("47.4535553199711 8.558652269898 -173.983079910278"),
+            Ok(GeoPosition {
+                latitude: 47.4535553199711,
+                longitude: 8.558652269898,
+                heading: Some(-173.983079910278),
+            })
+        );
+        assert_eq!(
+            parse_position("47.453456337199 8.55863758936658").unwrap().heading,
+            None
+        );
+        assert_eq!(
+            parse_position("47.45").unwrap_err().kind,
+            ValueErrorKind::WrongCount {
+                expected: "2 or 3",
+                found: 1
+            }
+        );
+        assert_eq!(
+            parse_position("47.45 abc 3").unwrap_err().kind,
+            ValueErrorKind::InvalidNumber(String::from("abc"))
+        );
+        assert_eq!(
+            parse_position("97.45 8.5 3").unwrap_err().kind,
+            ValueErrorKind::OutOfRange(String::from("97.45"))
+        );
+        assert!(parse_position("").is_err());
+    }
+
+    #[test]
+    fn object_positions() {
+        assert_eq!(
+            parse_object_position("47.4536607552555 8.55866945801215 -173.983079910278 1.18"),
+            Ok(ObjectPosition {
+                latitude: 47.4536607552555,
+                longitude: 8.55866945801215,
+                heading: -173.983079910278,
+                height: 1.18,
+            })
+        );
+        assert!(parse_object_position("47.45 8.55 -173.98").is_err());
+    }
+
+    #[test]
+    fn labels_int_lists_and_bools() {
+        assert_eq!(
+            parse_labels("Nose Right/Tail Left (LEFT)|Facing W on Taxi N"),
+            vec!["Nose Right/Tail Left (LEFT)", "Facing W on Taxi N"]
+        );
+        assert!(parse_labels("").is_empty());
+
+        assert_eq!(parse_airline_codes("SWR, DLH EDW"), vec!["SWR", "DLH", "EDW"]);
+        assert!(parse_airline_codes(" ").is_empty());
+
+        assert!(is_position_key("this_parking_pos"));
+        assert!(is_position_key("parkingsystem_objectposition"));
+        assert!(is_position_key("pushbackaddpos"));
+        assert!(!is_position_key("snapleftpushbackpos"));
+        assert!(!is_position_key("pushbacklabels"));
+
+        assert_eq!(parse_int_list("3 3 2"), Ok(vec![3, 3, 2]));
+        assert_eq!(parse_int_list(""), Ok(vec![]));
+        assert_eq!(
+            parse_int_list("3 x 2").unwrap_err().kind,
+            ValueErrorKind::InvalidNumber(String::from("x"))
+        );
+
+        assert_eq!(parse_bool("1"), Ok(true));
+        assert_eq!(parse_bool("0"), Ok(false));
+        assert_eq!(parse_bool("2").unwrap_err().kind, ValueErrorKind::InvalidBool);
+    }
+
+    #[test]
+    fn tuple_lists() {
+        let deice_areas = parse_tuple_list(r#"[(47.46, 8.56, 96, 20, "Rwy 10/28/32-C1","VgdsDeIceWall"),(47.45, 8.54, 334, 20, 'Rwy 16-F3', "Vgds\"Wall")]"#).unwrap();
+        assert_eq!(deice_areas.len(), 2);
+        assert_eq!(
+            deice_areas[0],
+            vec![
+                TupleValue::Number(47.46),
+                TupleValue::Number(8.56),
+                TupleValue::Number(96.0),
+                TupleValue::Number(20.0),
+                TupleValue::Text(String::from("Rwy 10/28/32-C1")),
+                TupleValue::Text(String::from("VgdsDeIceWall")),
+            ]
+        );
+        assert_eq!(deice_areas[1][4].as_str(), Some("Rwy 16-F3"));
+        assert_eq!(deice_areas[1][5].as_str(), Some("Vgds\"Wall"));
+
+        assert_eq!(parse_tuple_list("[]"), Ok(vec![]));
+        assert_eq!(parse_tuple_list(" [ ] "), Ok(vec![]));
+        assert_eq!(
+            parse_tuple_list("[(1, 2)"),
+            Err(ValueError::new(
+                "[(1, 2)",
+                ValueErrorKind::UnexpectedCharacter {
+                    offset: 7,
+                    found: None
+                }
+            ))
+        );
+        assert_eq!(
+            parse_tuple_list(r#"[(1, "a)]"#).unwrap_err().kind,
+            ValueErrorKind::UnterminatedString
+        );
+        assert!(parse_tuple_list("(1, 2)").is_err());
+        assert!(parse_tuple_list("[(1, 2)] x").is_err());
+        assert!(parse_tuple_list("[(1,, 2)]").is_err());
+    }
+
+    #[test]
+    fn deice_areas() {
+        let deice_areas = parse_deice_areas(
+            r#"[(47.46, 8.56, 96, 20, "Rwy 10/28/32-C1","VgdsDeIceWall"),(47.45, 8.54, 334, 20, "Rwy 16-F3")]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            deice_areas[0],
+            DeiceArea {
+                latitude: 47.46,
+                longitude: 8.56,
+                heading: 96.0,
+                size: 20.0,
+                label: String::from("Rwy 10/28/32-C1"),
+                object_type: Some(String::from("VgdsDeIceWall")),
+            }
+        );
+        assert_eq!(deice_areas[1].object_type, None);
+
+        assert_eq!(parse_deice_areas("[]"), Ok(vec![]));
+        assert_eq!(
+            parse_deice_areas("[(47.46, 8.56, 96)]").unwrap_err().kind,
+            ValueErrorKind::WrongCount {
+                expected: "5 or 6",
+                found: 3
+            }
+        );
+        assert_eq!(
+            parse_deice_areas(r#"[("47.46", 8.56, 96, 20, "A")]"#).unwrap_err().kind,
+            ValueErrorKind::InvalidNumber(String::from("47.46"))
+        );
+        assert!(parse_deice_areas(r#"[(147.46, 8.56, 96, 20, "A")]"#).is_err());
+    }
+
+    #[test]
+    fn pushback_add_positions() {
+        let positions = parse_pushback_add_positions(
+            "[{'snap': False, 'approach': [(47.4603, 8.5529, 6.0), (47.4598, 8.5518, 95.9), None, None], 'pos': (47.4602, 8.5507, 155.0), 'label': u'LONG - Facing SE on Taxi F'}]",
+        )
+        .unwrap();
+        assert_eq!(
+            positions,
+            vec![PushbackAddPosition {
+                label: String::from("LONG - Facing SE on Taxi F"),
+                snap: false,
+                approach: vec![
+                    GeoPosition {
+                        latitude: 47.4603,
+                        longitude: 8.5529,
+                        heading: Some(6.0)
+                    },
+                    GeoPosition {
+                        latitude: 47.4598,
+                        longitude: 8.5518,
+                        heading: Some(95.9)
+                    },
+                ],
+                position: GeoPosition {
+                    latitude: 47.4602,
+                    longitude: 8.5507,
+                    heading: Some(155.0)
+                },
+            }]
+        );
+
+        assert_eq!(parse_pushback_add_positions("[]"), Ok(vec![]));
+        assert_eq!(
+            parse_pushback_add_positions("[{'label': 'x'}]").unwrap_err().kind,
+            ValueErrorKind::InvalidField("pos")
+        );
+        assert!(parse_pushback_add_positions("[{'pos': (1, 2, 3)}").is_err());
+        assert!(parse_pushback_add_positions("[{'pos': (1, 2, 3)}] x").is_err());
+    }
+
+    #[test]
+    fn section_accessors() {
+        let document = crate::parse_document_str(
+            "[gate a 2]\npushback_pos = 47.45 8.55 -173.9\nhasjetway = 1\nloadertype = 3 x\n",
+        )
+        .document;
+        let section = document.section("gate a 2").unwrap();
+
+        assert_eq!(
+            section.get_position("pushback_pos").unwrap().unwrap().heading,
+            Some(-173.9)
+        );
+        assert!(section.get_position("jetway_pos").is_none());
+        assert_eq!(section.get_bool("hasjetway"), Some(Ok(true)));
+        assert!(section.get_int_list("loadertype").unwrap().is_err());
+    }
+}
@@ -1,15 +1,19 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 use eframe::egui;
 use egui::{Context, Style, Vec2, Visuals};
-use json::JsonValue;
 use uuid::Uuid;
 use walkers::{sources, MapMemory, Tiles};
 
-use GsxmanCore::{constants, Airport, ProfileFile};
+use GsxmanCore::{constants, Airport, PendingMerge, ProfileFile};
 
+use crate::app::ui::detail_columns::{DetailColumn, DetailSort};
 use crate::app::ui::UIState;
-use crate::core::{self as GsxmanCore, GsxSection};
+use crate::core::{
+    self as GsxmanCore,
+    settings::{ProfileRoot, UserData},
+    GsxSection,
+};
 
 use self::ui::plugins::ClickWatcher;
 
@@ -18,15 +22,21 @@ mod ui;
 struct GsxmanApp {
     map_memory: MapMemory,
     tiles: Tiles,
+    profile_roots: Vec<ProfileRoot>,
     installed_gsx_profiles: HashMap<Uuid, ProfileFile>,
     airport_data: HashMap<String, Airport>,
-    user_data: JsonValue,
+    user_data: UserData,
     click_watcher: ui::plugins::ClickWatcher,
     selected_profile_id: Option<Uuid>,
     selected_section_id: Option<Uuid>,
     scroll_to_row: Option<usize>,
     ui_state: UIState,
     filter_text: String,
+    /// Only profiles from this folder are shown in the overview.
+    root_filter: Option<PathBuf>,
+    detail_columns: Vec<DetailColumn>,
+    detail_sort: Option<DetailSort>,
+    pending_merge: Option<PendingMerge>,
 }
 
 impl GsxmanApp {
@@ -34,12 +44,15 @@ impl GsxmanApp {
         let airport_data = GsxmanCore::filehandling::get_airport_data();
         let map_memory = MapMemory::default();
         let user_data = GsxmanCore::filehandling::get_user_data();
-        Self {
+        let profile_roots = GsxmanCore::filehandling::get_gsx_profile_roots(&user_data.settings);
+        let mut app = Self {
             map_memory,
             tiles: Tiles::new(sources::OpenStreetMap, egui_ctx),
             installed_gsx_profiles: GsxmanCore::filehandling::get_installed_gsx_profiles(
                 &airport_data,
+                &profile_roots,
             ),
+            profile_roots,
             airport_data,
             user_data,
             click_watcher: ClickWatcher {
@@ -52,18 +65,26 @@ impl GsxmanApp {
             scroll_to_row: None,
             ui_state: UIState::Overview,
             filter_text: String::new(),
-        }
+            root_filter: None,
+            detail_columns: DetailColumn::DEFAULT.to_vec(),
+            detail_sort: None,
+            pending_merge: None,
+        };
+        app.attach_user_data();
+        app
     }
 
     fn update_installed_gsx_profiles(&mut self, profile_added: bool) {
-        let profiles_in_folder =
-            GsxmanCore::filehandling::get_installed_gsx_profiles(&self.airport_data);
+        let profiles_in_folder = GsxmanCore::filehandling::get_installed_gsx_profiles(
+            &self.airport_data,
+            &self.profile_roots,
+        );
 
         if profile_added {
             for (id, profile) in profiles_in_folder.iter() {
                 let mut profile_exists = false;
                 for installed_profile in self.installed_gsx_profiles.values().into_iter() {
-                    if installed_profile.file_name == profile.file_name {
+                    if installed_profile.file_location == profile.file_location {
                         profile_exists = true;
                     }
                 }
@@ -76,13 +97,47 @@ impl GsxmanApp {
         } else {
             self.installed_gsx_profiles.retain(|_, profile| {
                 for profile_in_folder in profiles_in_folder.values().into_iter() {
-                    if profile.file_name == profile_in_folder.file_name {
+                    if profile.file_location == profile_in_folder.file_location {
                         return true;
                     }
                 }
                 return false;
             });
         }
+        self.attach_user_data();
+    }
+
+    /// Reads the profile folders from the settings again and all profiles in them.
+    fn reload_profile_roots(&mut self) {
+        self.profile_roots =
+            GsxmanCore::filehandling::get_gsx_profile_roots(&self.user_data.settings);
+        self.installed_gsx_profiles = GsxmanCore::filehandling::get_installed_gsx_profiles(
+            &self.airport_data,
+            &self.profile_roots,
+        );
+        self.selected_profile_id = None;
+        self.selected_section_id = None;
+        let profile_roots = &self.profile_roots;
+        self.root_filter = self
+            .root_filter
+            .take()
+            .filter(|root_filter| profile_roots.iter().any(|root| root.path == *root_filter));
+        self.attach_user_data();
+    }
+
+    /// The folder new profiles are imported to: the one the overview is filtered by, or else the
+    /// first one.
+    fn get_import_root(&self) -> Option<PathBuf> {
+        self.root_filter
+            .clone()
+            .or_else(|| self.profile_roots.first().map(|root| root.path.clone()))
+    }
+
+    /// Finds the user data of profiles that were renamed, moved or re-imported.
+    fn attach_user_data(&mut self) {
+        if self.user_data.attach_profiles(self.installed_gsx_profiles.values()) {
+            GsxmanCore::filehandling::write_user_data(&self.user_data);
+        }
     }
 
     fn get_selected_profile(&self) -> Option<&ProfileFile> {
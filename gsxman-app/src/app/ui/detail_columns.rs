@@ -0,0 +1,175 @@
+use std::cmp::Ordering;
+
+use crate::core::{attributes::Flag, GsxSection};
+
+use super::heading_text;
+
+/// A column of the details table after the location name. Only some are shown by default, the
+/// others can be turned on with the column chooser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailColumn {
+    Latitude,
+    Longitude,
+    Heading,
+    ParkingType,
+    Pushback,
+    ParkingSystem,
+    HasJetway,
+    MaxWingspan,
+    RadiusLeft,
+    RadiusRight,
+    GateDistanceThreshold,
+    AirlineCodes,
+    NoPassengerStairs,
+    NoPassengerBus,
+    UndergroundRefueling,
+    UserCustomized,
+}
+
+/// The column the details table is sorted by. Without one the stands are grouped by area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetailSort {
+    pub column: DetailColumn,
+    pub ascending: bool,
+}
+
+impl DetailColumn {
+    pub const ALL: [DetailColumn; 16] = [
+        DetailColumn::Latitude,
+        DetailColumn::Longitude,
+        DetailColumn::Heading,
+        DetailColumn::ParkingType,
+        DetailColumn::Pushback,
+        DetailColumn::ParkingSystem,
+        DetailColumn::HasJetway,
+        DetailColumn::MaxWingspan,
+        DetailColumn::RadiusLeft,
+        DetailColumn::RadiusRight,
+        DetailColumn::GateDistanceThreshold,
+        DetailColumn::AirlineCodes,
+        DetailColumn::NoPassengerStairs,
+        DetailColumn::NoPassengerBus,
+        DetailColumn::UndergroundRefueling,
+        DetailColumn::UserCustomized,
+    ];
+
+    pub const DEFAULT: [DetailColumn; 7] = [
+        DetailColumn::Latitude,
+        DetailColumn::Longitude,
+        DetailColumn::Heading,
+        DetailColumn::ParkingType,
+        DetailColumn::Pushback,
+        DetailColumn::ParkingSystem,
+        DetailColumn::HasJetway,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            DetailColumn::Latitude => "Latitude",
+            DetailColumn::Longitude => "Longitude",
+            DetailColumn::Heading => "Heading",
+            DetailColumn::ParkingType => "Type",
+            DetailColumn::Pushback => "Pushback",
+            DetailColumn::ParkingSystem => "Parking system",
+            DetailColumn::HasJetway => "Jetway",
+            DetailColumn::MaxWingspan => "Max wingspan",
+            DetailColumn::RadiusLeft => "Radius left",
+            DetailColumn::RadiusRight => "Radius right",
+            DetailColumn::GateDistanceThreshold => "Gate distance",
+            DetailColumn::AirlineCodes => "Airlines",
+            DetailColumn::NoPassengerStairs => "No stairs",
+            DetailColumn::NoPassengerBus => "No bus",
+            DetailColumn::UndergroundRefueling => "Underground fuel",
+            DetailColumn::UserCustomized => "Customized",
+        }
+    }
+
+    /// The readable value of the column for a section, with the value as it is written in the
+    /// profile if that is different.
+    pub fn value(self, section: &GsxSection) -> Option<(String, Option<String>)> {
+        let flag = |flag: &Option<Flag>| {
+            flag.as_ref()
+                .map(|flag| (flag.to_string(), Some(flag.to_value())))
+        };
+        let meters = |meters: Option<f64>| meters.map(|meters| (format!("{} m", meters), None));
+        match self {
+            DetailColumn::Latitude | DetailColumn::Longitude | DetailColumn::Heading => {
+                let text = match section.position {
+                    Some(gsx_position) => match self {
+                        DetailColumn::Latitude => gsx_position.position.lat().to_string(),
+                        DetailColumn::Longitude => gsx_position.position.lon().to_string(),
+                        _ => heading_text(gsx_position.heading),
+                    },
+                    None => String::from("-"),
+                };
+                Some((text, None))
+            }
+            DetailColumn::ParkingType => section
+                .parking_type
+                .as_ref()
+                .map(|parking_type| (parking_type.to_string(), Some(parking_type.to_value()))),
+            DetailColumn::Pushback => section
+                .pushback_directions
+                .as_ref()
+                .map(|pushback| (pushback.to_string(), Some(pushback.to_value()))),
+            DetailColumn::ParkingSystem => section
+                .parking_system
+                .as_ref()
+                .map(|parking_system| (parking_system.to_string(), Some(parking_system.to_value()))),
+            DetailColumn::HasJetway => flag(&section.has_jetway),
+            DetailColumn::MaxWingspan => meters(section.max_wingspan),
+            DetailColumn::RadiusLeft => meters(section.radius_left),
+            DetailColumn::RadiusRight => meters(section.radius_right),
+            DetailColumn::GateDistanceThreshold => meters(section.gate_distance_threshold),
+            DetailColumn::AirlineCodes if section.airline_codes.is_empty() => None,
+            DetailColumn::AirlineCodes => Some((section.airline_codes.join(", "), None)),
+            DetailColumn::NoPassengerStairs => flag(&section.no_passenger_stairs),
+            DetailColumn::NoPassengerBus => flag(&section.no_passenger_bus),
+            DetailColumn::UndergroundRefueling => flag(&section.underground_refueling),
+            DetailColumn::UserCustomized => flag(&section.user_customized),
+        }
+    }
+
+    /// Numbers are compared as numbers, everything else by its readable value. Sections without a
+    /// value come last.
+    pub fn compare(self, a: &GsxSection, b: &GsxSection) -> Ordering {
+        match (self.number(a), self.number(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) if self.number_column() => Ordering::Equal,
+            (None, None) => match (self.value(a), self.value(b)) {
+                (Some((a, _)), Some((b, _))) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        }
+    }
+
+    fn number(self, section: &GsxSection) -> Option<f64> {
+        match self {
+            DetailColumn::Latitude => section.position.map(|p| p.position.lat()),
+            DetailColumn::Longitude => section.position.map(|p| p.position.lon()),
+            DetailColumn::Heading => section.position.and_then(|p| p.heading),
+            DetailColumn::MaxWingspan => section.max_wingspan,
+            DetailColumn::RadiusLeft => section.radius_left,
+            DetailColumn::RadiusRight => section.radius_right,
+            DetailColumn::GateDistanceThreshold => section.gate_distance_threshold,
+            _ => None,
+        }
+    }
+
+    fn number_column(self) -> bool {
+        matches!(
+            self,
+            DetailColumn::Latitude
+                | DetailColumn::Longitude
+                | DetailColumn::Heading
+                | DetailColumn::MaxWingspan
+                | DetailColumn::RadiusLeft
+                | DetailColumn::RadiusRight
+                | DetailColumn::GateDistanceThreshold
+        )
+    }
+}
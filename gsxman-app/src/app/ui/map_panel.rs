@@ -1,4 +1,5 @@
-use egui::{Color32, Ui, Vec2};
+use egui::{Color32, Stroke, Ui, Vec2};
+use gsx_ini_parser::DeiceArea;
 use itertools::Itertools;
 use walkers::{
     extras::{Place, Places, Style},
@@ -7,11 +8,21 @@ use walkers::{
 
 use crate::{
     app::ui::UIState,
-    core::{filehandling, ProfileFile},
+    core::{
+        conflicts::{stand_center, wingspan_radius, ConflictKind},
+        filehandling, GsxPosition, GsxSection, ProfileFile,
+    },
 };
 use crate::{app::GsxmanApp, core::GsxPlace};
 
-use super::{filter_profile_details, filter_profiles, plugins::NoteDrawer};
+use super::{
+    detail_rows, filter_profile_details, filter_profiles, group_colors,
+    plugins::{
+        AreaLabelDrawer, DeiceAreaDrawer, HeadingTickDrawer, NoteDrawer, PushbackPathDrawer,
+        WingspanCircleDrawer,
+    },
+    pushback_color, DetailRow, CONFLICT_COLOR,
+};
 
 fn handle_scrolling(app: &mut GsxmanApp, ui: &mut Ui) {
     let position = {
@@ -63,9 +74,8 @@ pub fn update_map_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                     filehandling::load_profile_data(selected_profile);
                 }
 
-                let profile_file_location = selected_profile.file_location.as_os_str().to_str().unwrap();
-                if user_data[profile_file_location] != json::Null {
-                    selected_profile.notes = user_data[profile_file_location]["notes"].to_string();
+                if let Some(profile_user_data) = user_data.profile(selected_profile) {
+                    selected_profile.notes = profile_user_data.notes.clone();
                 }
                 result = Some(selected_profile.to_owned());
             }
@@ -74,6 +84,11 @@ pub fn update_map_panel(app: &mut GsxmanApp, ui: &mut Ui) {
         result
     };
     let note_drawer = NoteDrawer::new(selected_profile);
+    let area_label_drawer = AreaLabelDrawer::new(get_area_labels(app));
+    let deice_area_drawer = DeiceAreaDrawer::new(get_deice_areas(app));
+    let pushback_path_drawer = PushbackPathDrawer::new(get_pushback_paths(app));
+    let heading_tick_drawer = HeadingTickDrawer::new(get_heading_ticks(app));
+    let wingspan_circle_drawer = WingspanCircleDrawer::new(get_wingspan_circles(app));
 
     // Manual Zoom by Scrolling. Map Library only allows Zooming by holding Ctrl
     handle_scrolling(app, ui);
@@ -85,21 +100,29 @@ pub fn update_map_panel(app: &mut GsxmanApp, ui: &mut Ui) {
             Position::from_lat_lon(52.0, 0.0),
         )
         .zoom_gesture(false)
+        .with_plugin(deice_area_drawer)
+        .with_plugin(wingspan_circle_drawer)
+        .with_plugin(pushback_path_drawer)
+        .with_plugin(heading_tick_drawer)
         .with_plugin(places)
         .with_plugin(&mut app.click_watcher)
-        .with_plugin(note_drawer),
+        .with_plugin(note_drawer)
+        .with_plugin(area_label_drawer),
     );
 
     if app.click_watcher.has_clicked {
         if let Some(clicked_label) = &app.click_watcher.clicked_label {
             let filter_text = app.filter_text.clone();
+            let root_filter = app.root_filter.clone();
             match app.ui_state {
                 UIState::Overview => {
                     for (row_index, (_, profile)) in app
                         .installed_gsx_profiles
                         .iter()
                         .sorted_by(|a, b| Ord::cmp(&a.1.airport.icao, &b.1.airport.icao))
-                        .filter(|&(_, profile)| filter_profiles(&filter_text, profile))
+                        .filter(|&(_, profile)| {
+                            filter_profiles(&filter_text, root_filter.as_deref(), profile)
+                        })
                         .enumerate()
                     {
                         if *clicked_label == profile.airport.icao {
@@ -112,13 +135,14 @@ pub fn update_map_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                 UIState::Details => {
                     for (_, profile) in app.installed_gsx_profiles.iter() {
                         if let Some(profile_data) = profile.profile_data.as_ref() {
-                            for (row_index, section) in profile_data
-                                .sections
-                                .iter()
-                                .sorted_by(|a, b| Ord::cmp(&a.name, &b.name))
-                                .filter(|&section| filter_profile_details(&filter_text, section))
-                                .enumerate()
+                            for (row_index, detail_row) in
+                                detail_rows(&profile_data.sections, &filter_text, app.detail_sort)
+                                    .iter()
+                                    .enumerate()
                             {
+                                let DetailRow::Section(section) = detail_row else {
+                                    continue;
+                                };
                                 if *clicked_label == section.name {
                                     app.selected_section_id = Some(section.id);
                                     app.scroll_to_row = Some(row_index);
@@ -130,6 +154,10 @@ pub fn update_map_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                 }
                 UIState::SectionDetails => (),
                 UIState::Notes => (),
+                UIState::Problems => (),
+                UIState::OrphanedNotes => (),
+                UIState::ProfileRoots => (),
+                UIState::Merge => (),
             }
         } else {
             match app.ui_state {
@@ -137,6 +165,10 @@ pub fn update_map_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                 UIState::Details => app.selected_section_id = None,
                 UIState::SectionDetails => (),
                 UIState::Notes => (),
+                UIState::Problems => (),
+                UIState::OrphanedNotes => (),
+                UIState::ProfileRoots => (),
+                UIState::Merge => (),
             }
         }
 
@@ -150,6 +182,10 @@ fn get_places_to_display(app: &mut GsxmanApp) -> Vec<GsxPlace> {
         UIState::Details => get_airport_detail_places(app),
         UIState::SectionDetails => get_section_detail_places(app),
         UIState::Notes => get_airport_detail_places(app),
+        UIState::Problems => get_airport_detail_places(app),
+        UIState::OrphanedNotes => get_airport_places(app),
+        UIState::ProfileRoots => get_airport_places(app),
+        UIState::Merge => get_airport_places(app),
     }
 }
 
@@ -158,58 +194,60 @@ fn get_section_detail_places(app: &mut GsxmanApp) -> Vec<GsxPlace> {
     let filter_text_str = filter_text.as_str();
     let mut places: Vec<GsxPlace> = vec![];
     if let Some(selected_section) = app.get_selected_section() {
-        places.push(GsxPlace(Place {
-            label: selected_section.name.to_owned(),
-            position: Position::from_lat_lon(
-                selected_section.position.lat(),
-                selected_section.position.lon(),
-            ),
-            symbol: '✈',
-            style: Style {
-                label_background: Color32::BLACK.gamma_multiply(0.8),
-                symbol_background: Color32::WHITE.gamma_multiply(0.8),
-                ..Default::default()
-            },
-        }));
-        if let Some(pushback_position_left) = &selected_section.pushback_position_left {
-            if let Some(pushback_label_left) = &selected_section.pushback_label_left {
-                if pushback_label_left.to_lowercase().contains(filter_text_str) {
-                    places.push(GsxPlace(Place {
-                        label: pushback_label_left.to_owned(),
-                        position: Position::from_lat_lon(
-                            pushback_position_left.lat(),
-                            pushback_position_left.lon(),
-                        ),
-                        symbol: '🖈',
-                        style: Style {
-                            label_background: Color32::BLACK.gamma_multiply(0.8),
-                            symbol_background: Color32::WHITE.gamma_multiply(0.8),
-                            ..Default::default()
-                        },
-                    }));
-                }
+        if let Some(gsx_position) = selected_section.position {
+            places.push(GsxPlace(Place {
+                label: selected_section.name.to_owned(),
+                position: gsx_position.position,
+                symbol: '✈',
+                style: Style {
+                    label_background: Color32::BLACK.gamma_multiply(0.8),
+                    symbol_background: Color32::WHITE.gamma_multiply(0.8),
+                    ..Default::default()
+                },
+            }));
+        }
+        for other in conflicting_sections(app, selected_section) {
+            if let Some(gsx_position) = other.position {
+                places.push(GsxPlace(Place {
+                    label: other.name.to_owned(),
+                    position: gsx_position.position,
+                    symbol: '✈',
+                    style: Style {
+                        label_background: CONFLICT_COLOR.gamma_multiply(0.8),
+                        symbol_background: Color32::WHITE.gamma_multiply(0.8),
+                        ..Default::default()
+                    },
+                }));
             }
         }
-        if let Some(pushback_position_right) = &selected_section.pushback_position_right {
-            if let Some(pushback_label_right) = &selected_section.pushback_label_right {
-                if pushback_label_right
-                    .to_lowercase()
-                    .contains(filter_text_str)
-                {
-                    places.push(GsxPlace(Place {
-                        label: pushback_label_right.to_owned(),
-                        position: Position::from_lat_lon(
-                            pushback_position_right.lat(),
-                            pushback_position_right.lon(),
-                        ),
-                        symbol: '🖈',
-                        style: Style {
-                            label_background: Color32::BLACK.gamma_multiply(0.8),
-                            symbol_background: Color32::WHITE.gamma_multiply(0.8),
-                            ..Default::default()
-                        },
-                    }));
-                }
+        for (name, gsx_position) in selected_section.service_positions() {
+            if name.to_lowercase().contains(filter_text_str) {
+                places.push(GsxPlace(Place {
+                    label: String::from(name),
+                    position: gsx_position.position,
+                    symbol: '•',
+                    style: Style {
+                        label_background: Color32::DARK_GRAY.gamma_multiply(0.8),
+                        symbol_background: Color32::LIGHT_GRAY.gamma_multiply(0.8),
+                        ..Default::default()
+                    },
+                }));
+            }
+        }
+        for (index, pushback_path) in selected_section.pushback_paths.iter().enumerate() {
+            let label = pushback_path.label();
+            if label.to_lowercase().contains(filter_text_str) {
+                places.push(GsxPlace(Place {
+                    label,
+                    position: pushback_path.end.position,
+                    symbol: '🖈',
+                    style: Style {
+                        label_background: Color32::BLACK.gamma_multiply(0.8),
+                        symbol_background: Color32::WHITE.gamma_multiply(0.8),
+                        symbol_stroke: Stroke::new(2.0, pushback_color(index)),
+                        ..Default::default()
+                    },
+                }));
             }
         }
     }
@@ -220,14 +258,18 @@ fn get_airport_detail_places(app: &mut GsxmanApp) -> Vec<GsxPlace> {
     let filter_text = app.filter_text.clone();
     let mut places: Vec<GsxPlace> = vec![];
     if let Some(profile_data) = &app.get_selected_profile().as_ref().unwrap().profile_data {
+        let group_colors = group_colors(&profile_data.sections);
         for section in profile_data
             .sections
             .iter()
             .filter(|&section| filter_profile_details(&filter_text, section))
         {
+            let Some(gsx_position) = section.position else {
+                continue;
+            };
             places.push(GsxPlace(Place {
                 label: section.name.to_owned(),
-                position: Position::from_lat_lon(section.position.lat(), section.position.lon()),
+                position: gsx_position.position,
                 symbol: '🖈',
                 style: Style {
                     label_background: if let Some(selected_section_id) =
@@ -252,6 +294,12 @@ fn get_airport_detail_places(app: &mut GsxmanApp) -> Vec<GsxPlace> {
                     } else {
                         Color32::WHITE.gamma_multiply(0.8)
                     },
+                    symbol_stroke: Stroke::new(3.0, group_colors[&section.group()]),
+                    label_color: if section.conflicts.is_empty() {
+                        Style::default().label_color
+                    } else {
+                        CONFLICT_COLOR
+                    },
                     ..Default::default()
                 },
             }));
@@ -261,13 +309,227 @@ fn get_airport_detail_places(app: &mut GsxmanApp) -> Vec<GsxPlace> {
     places
 }
 
+/// Positions with a heading tick: the stands in the details view, the stand and the ends of its
+/// pushback options in the section details view.
+fn get_heading_ticks(app: &GsxmanApp) -> Vec<(GsxPosition, Color32)> {
+    let filter_text = app.filter_text.to_lowercase();
+    match app.ui_state {
+        UIState::Details => app
+            .get_selected_profile()
+            .and_then(|profile| profile.profile_data.as_ref())
+            .map(|profile_data| {
+                profile_data
+                    .sections
+                    .iter()
+                    .filter(|&section| filter_profile_details(&app.filter_text, section))
+                    .filter_map(|section| section.position)
+                    .map(|gsx_position| (gsx_position, Color32::WHITE))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        UIState::SectionDetails => {
+            let Some(selected_section) = app.get_selected_section() else {
+                return vec![];
+            };
+            let pushback_ends = selected_section
+                .pushback_paths
+                .iter()
+                .enumerate()
+                .filter(|(_, pushback_path)| pushback_path.label().to_lowercase().contains(&filter_text))
+                .map(|(index, pushback_path)| (pushback_path.end, pushback_color(index)));
+            selected_section
+                .position
+                .map(|gsx_position| (gsx_position, Color32::WHITE))
+                .into_iter()
+                .chain(pushback_ends)
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// Waypoints and color of every pushback option of the selected section, shown in the section
+/// details view, and the pushbacks of other stands that cross them.
+fn get_pushback_paths(app: &GsxmanApp) -> Vec<(Vec<Position>, Color32)> {
+    if app.ui_state != UIState::SectionDetails {
+        return vec![];
+    }
+    let Some(selected_section) = app.get_selected_section() else {
+        return vec![];
+    };
+    let filter_text = app.filter_text.to_lowercase();
+    let crossing_paths = conflicting_sections(app, selected_section)
+        .into_iter()
+        .flat_map(|other| {
+            other.pushback_paths.iter().filter(|other_pushback_path| {
+                selected_section.conflicts.iter().any(|conflict| {
+                    conflict.other_section_id == other.id
+                        && matches!(
+                            &conflict.kind,
+                            ConflictKind::PushbackPathsCross { other_pushback, .. }
+                                if *other_pushback == other_pushback_path.label()
+                        )
+                })
+            })
+        })
+        .map(|pushback_path| {
+            let waypoints = pushback_path
+                .waypoints()
+                .iter()
+                .map(|waypoint| waypoint.position)
+                .collect();
+            (waypoints, CONFLICT_COLOR)
+        })
+        .collect::<Vec<_>>();
+    selected_section
+        .pushback_paths
+        .iter()
+        .enumerate()
+        .filter(|(_, pushback_path)| {
+            pushback_path
+                .label()
+                .to_lowercase()
+                .contains(&filter_text)
+        })
+        .map(|(index, pushback_path)| {
+            let waypoints = pushback_path
+                .waypoints()
+                .iter()
+                .map(|waypoint| waypoint.position)
+                .collect();
+            (waypoints, pushback_color(index))
+        })
+        .chain(crossing_paths)
+        .collect()
+}
+
+/// Wingspan circles of the stands whose circles overlap in the details view. In the section
+/// details view the circle of the selected stand and of every stand it conflicts with.
+fn get_wingspan_circles(app: &GsxmanApp) -> Vec<(Position, f64, Color32)> {
+    let circle = |section: &GsxSection, color: Color32| {
+        Some((stand_center(section)?, wingspan_radius(section)?, color))
+    };
+    match app.ui_state {
+        UIState::Details => app
+            .get_selected_profile()
+            .and_then(|profile| profile.profile_data.as_ref())
+            .map(|profile_data| {
+                profile_data
+                    .sections
+                    .iter()
+                    .filter(|&section| filter_profile_details(&app.filter_text, section))
+                    .filter(|section| {
+                        section
+                            .conflicts
+                            .iter()
+                            .any(|conflict| matches!(conflict.kind, ConflictKind::WingspanOverlap(_)))
+                    })
+                    .filter_map(|section| circle(section, CONFLICT_COLOR))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        UIState::SectionDetails => {
+            let Some(selected_section) = app.get_selected_section() else {
+                return vec![];
+            };
+            circle(selected_section, Color32::WHITE)
+                .into_iter()
+                .chain(
+                    conflicting_sections(app, selected_section)
+                        .into_iter()
+                        .filter_map(|other| circle(other, CONFLICT_COLOR)),
+                )
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// The other stands of the selected profile `section` has a conflict with.
+fn conflicting_sections<'a>(app: &'a GsxmanApp, section: &GsxSection) -> Vec<&'a GsxSection> {
+    let Some(profile_data) = app
+        .get_selected_profile()
+        .and_then(|profile| profile.profile_data.as_ref())
+    else {
+        return vec![];
+    };
+    profile_data
+        .sections
+        .iter()
+        .filter(|other| {
+            section
+                .conflicts
+                .iter()
+                .any(|conflict| conflict.other_section_id == other.id)
+        })
+        .collect()
+}
+
+/// De-ice areas of the selected profile, shown in the details view.
+fn get_deice_areas(app: &GsxmanApp) -> Vec<DeiceArea> {
+    if app.ui_state != UIState::Details {
+        return vec![];
+    }
+    let filter_text = app.filter_text.to_lowercase();
+    app.get_selected_profile()
+        .and_then(|profile| profile.profile_data.as_ref())
+        .map(|profile_data| {
+            profile_data
+                .deice_areas
+                .iter()
+                .filter(|deice_area| deice_area.label.to_lowercase().contains(&filter_text))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Name, color and center of every area of the selected profile, shown in the details view.
+fn get_area_labels(app: &GsxmanApp) -> Vec<(String, Color32, Position)> {
+    if app.ui_state != UIState::Details {
+        return vec![];
+    }
+    let Some(profile_data) = app
+        .get_selected_profile()
+        .and_then(|profile| profile.profile_data.as_ref())
+    else {
+        return vec![];
+    };
+
+    let group_colors = group_colors(&profile_data.sections);
+    let mut area_labels = vec![];
+    for (group, sections) in &profile_data
+        .sections
+        .iter()
+        .filter(|&section| filter_profile_details(&app.filter_text, section))
+        .sorted_by_key(|section| section.group())
+        .chunk_by(|section| section.group())
+    {
+        let positions: Vec<Position> = sections
+            .filter_map(|section| section.position)
+            .map(|gsx_position| gsx_position.position)
+            .collect();
+        if positions.is_empty() {
+            continue;
+        }
+        let count = positions.len() as f64;
+        let center = Position::from_lat_lon(
+            positions.iter().map(|position| position.lat()).sum::<f64>() / count,
+            positions.iter().map(|position| position.lon()).sum::<f64>() / count,
+        );
+        area_labels.push((group.clone(), group_colors[&group], center));
+    }
+    area_labels
+}
+
 fn get_airport_places(app: &mut GsxmanApp) -> Vec<GsxPlace> {
     let filter_text = app.filter_text.clone();
+    let root_filter = app.root_filter.clone();
     let mut places: Vec<GsxPlace> = Vec::new();
     for (_, profile) in app
         .installed_gsx_profiles
         .iter()
-        .filter(|&(_, profile)| filter_profiles(&filter_text, profile))
+        .filter(|&(_, profile)| filter_profiles(&filter_text, root_filter.as_deref(), profile))
     {
         places.push(GsxPlace(Place {
             label: profile.airport.icao.to_owned(),
@@ -1,7 +1,14 @@
 use egui::{menu, Ui};
 use walkers::Position;
 
-use crate::{app::GsxmanApp, core::filehandling};
+use crate::{
+    app::GsxmanApp,
+    core::{
+        filehandling,
+        settings::{ProfileRoot, ProfileRootSource},
+    },
+    util,
+};
 use crate::app::ui::UIState;
 
 use super::map_panel;
@@ -28,19 +35,50 @@ pub fn update_menu_bar_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                     app.filter_text.clear();
                 }
 
+                let selected_profile = app.get_selected_profile();
+                if ui.add_enabled(selected_profile.is_some(), egui::Button::new("Profile Problems")).clicked() {
+                    handle_profile_problems(app);
+                    app.filter_text.clear();
+                }
+
                 let selected_profile = app.get_selected_profile();
                 if ui.add_enabled(selected_profile.is_some(), egui::Button::new("Show Profile on Map")).clicked() {
                     handle_show_profile_on_map(app);
                 }
 
+                let orphan_count = app
+                    .user_data
+                    .orphaned_profiles(app.installed_gsx_profiles.values())
+                    .len();
+                let orphans_button = egui::Button::new(format!("Orphaned Notes ({})", orphan_count));
+                if ui.add_enabled(orphan_count > 0, orphans_button).clicked() {
+                    app.ui_state = UIState::OrphanedNotes;
+                    app.filter_text.clear();
+                }
+
                 if ui.button("Refresh Profiles").clicked() {
                     app.update_installed_gsx_profiles(false);
                 }
                 
-                if ui.button("Import new Profile").clicked() {
-                    filehandling::import_profile_file_dialog();
+                let import_root = app.get_import_root();
+                let import_button = egui::Button::new("Import new Profile");
+                let response = ui.add_enabled(import_root.is_some(), import_button);
+                let response = match &import_root {
+                    Some(import_root) => response.on_hover_text(format!("Imports into {}", import_root.display())),
+                    None => response.on_disabled_hover_text("Add a profile folder to import into first"),
+                };
+                if response.clicked() {
+                    if let Some(pending_merge) = filehandling::import_profile_file_dialog(&import_root.unwrap()) {
+                        app.pending_merge = Some(pending_merge);
+                        app.ui_state = UIState::Merge;
+                    }
                     app.update_installed_gsx_profiles(true);
                 }
+
+                if ui.button("Profile Folders").clicked() {
+                    app.ui_state = UIState::ProfileRoots;
+                    app.filter_text.clear();
+                }
             }
             UIState::Details => {
                 if ui.button("Back to Overview").clicked() {
@@ -64,14 +102,44 @@ pub fn update_menu_bar_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                     app.filter_text.clear();
                 }
             },
+            UIState::Problems | UIState::OrphanedNotes => {
+                if ui.button("Back to Overview").clicked() {
+                    app.ui_state = UIState::Overview;
+                    app.filter_text.clear();
+                }
+            }
+            UIState::ProfileRoots => {
+                if ui.button("Back to Overview").clicked() {
+                    app.ui_state = UIState::Overview;
+                    app.filter_text.clear();
+                }
+
+                if ui.button("Add Folder").clicked() {
+                    handle_add_profile_root(app);
+                }
+            }
+            UIState::Merge => {
+                if ui.button("Cancel Merge").clicked() {
+                    app.pending_merge = None;
+                    app.ui_state = UIState::Overview;
+                }
+
+                if ui.button("Apply Merge").clicked() {
+                    if let Some(pending_merge) = app.pending_merge.take() {
+                        filehandling::finish_merge(pending_merge);
+                    }
+                    app.ui_state = UIState::Overview;
+                    app.update_installed_gsx_profiles(false);
+                }
+            }
             UIState::Notes => {
                 if ui.button("Back to Overview").clicked() {
                     app.ui_state = UIState::Overview;
                     app.filter_text.clear();
 
                     let selected_profile = app.get_selected_profile().unwrap().clone();
-                    let profile_file_location = selected_profile.file_location.as_os_str().to_str().unwrap();
-                    app.user_data[profile_file_location]["notes"] = selected_profile.notes.into();
+                    app.user_data.profile_mut(&selected_profile).notes = selected_profile.notes.clone();
+                    app.user_data.remove_empty_profiles();
                     filehandling::write_user_data(&app.user_data);
                 }
             }
@@ -79,6 +147,25 @@ pub fn update_menu_bar_panel(app: &mut GsxmanApp, ui: &mut Ui) {
     });
 }
 
+fn handle_add_profile_root(app: &mut GsxmanApp) {
+    let Some(path) = rfd::FileDialog::new()
+        .set_title("Add GSX Profile Folder")
+        .pick_folder()
+    else {
+        return;
+    };
+    if app.profile_roots.iter().any(|root| root.path == path) {
+        return;
+    }
+    let label = util::get_folder_name(&path);
+    app.user_data
+        .settings
+        .profile_roots
+        .push(ProfileRoot::new(&label, path, ProfileRootSource::User));
+    filehandling::write_user_data(&app.user_data);
+    app.reload_profile_roots();
+}
+
 fn handle_profile_delete(app: &mut GsxmanApp) {
     let file_location = &app.get_selected_profile().unwrap().file_location;
     if filehandling::delete_profile_file(file_location) {
@@ -107,12 +194,10 @@ fn handle_profile_details(app: &mut GsxmanApp) {
 
 fn handle_section_details(app: &mut GsxmanApp) {
     if let Some(selected_section) = app.get_selected_section() {
-        let zoom_pos = Position::from_lat_lon(
-            selected_section.position.lat(),
-            selected_section.position.lon(),
-        );
-        map_panel::zoom_map_to_position(app, zoom_pos, 2);
-        
+        if let Some(gsx_position) = selected_section.position {
+            map_panel::zoom_map_to_position(app, gsx_position.position, 2);
+        }
+
         app.ui_state = UIState::SectionDetails;
     }
 }
@@ -124,9 +209,8 @@ fn handle_profile_notes(app: &mut GsxmanApp) {
             filehandling::load_profile_data(profile);
         }
 
-        let profile_file_location = profile.file_location.as_os_str().to_str().unwrap();
-        if user_data[profile_file_location] != json::Null {
-            profile.notes = user_data[profile_file_location]["notes"].to_string();
+        if let Some(profile_user_data) = user_data.profile(profile) {
+            profile.notes = profile_user_data.notes.clone();
         }
 
         if let Some(selected_profile) = app.get_selected_profile() {
@@ -141,6 +225,24 @@ fn handle_profile_notes(app: &mut GsxmanApp) {
     }
 }
 
+fn handle_profile_problems(app: &mut GsxmanApp) {
+    if let Some(profile) = app.get_selected_profile_mut() {
+        if profile.profile_data.is_none() {
+            filehandling::load_profile_data(profile);
+        }
+
+        if let Some(selected_profile) = app.get_selected_profile() {
+            let zoom_pos = Position::from_lat_lon(
+                selected_profile.airport.location.latitude(),
+                selected_profile.airport.location.longitude(),
+            );
+            map_panel::zoom_map_to_position(app, zoom_pos, 4);
+        }
+
+        app.ui_state = UIState::Problems;
+    }
+}
+
 fn handle_show_profile_on_map(app: &mut GsxmanApp) {
     if let Some(selected_profile) = app.get_selected_profile() {
         let zoom_pos = Position::from_lat_lon(
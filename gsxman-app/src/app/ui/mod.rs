@@ -1,9 +1,16 @@
-use egui::{Id, Margin};
+use std::{collections::HashMap, path::Path};
+
+use egui::{Color32, Id, Margin};
+use gsx_ini_parser::compare_stand_names;
+use itertools::Itertools;
 
 use crate::core::{constants, GsxSection, ProfileFile};
 
 use super::GsxmanApp;
 
+use self::detail_columns::DetailSort;
+
+pub mod detail_columns;
 mod map_panel;
 mod menu_bar_panel;
 pub mod plugins;
@@ -15,6 +22,10 @@ pub enum UIState {
     Details,
     SectionDetails,
     Notes,
+    Problems,
+    OrphanedNotes,
+    ProfileRoots,
+    Merge,
 }
 
 impl eframe::App for GsxmanApp {
@@ -61,9 +72,13 @@ impl eframe::App for GsxmanApp {
     }
 }
 
-fn filter_profiles(filter_text: &str, profile: &ProfileFile) -> bool {
+fn filter_profiles(filter_text: &str, root_filter: Option<&Path>, profile: &ProfileFile) -> bool {
     let mut should_display;
 
+    if root_filter.is_some_and(|root_filter| profile.root.path != root_filter) {
+        return false;
+    }
+
     if filter_text.is_empty() {
         return true;
     }
@@ -78,6 +93,10 @@ fn filter_profiles(filter_text: &str, profile: &ProfileFile) -> bool {
         should_display = profile.creator.to_lowercase().contains(filter_str);
     }
 
+    if !should_display {
+        should_display = profile.root.label.to_lowercase().contains(filter_str);
+    }
+
     should_display
 }
 
@@ -90,4 +109,94 @@ fn filter_profile_details(filter_text: &str, section: &GsxSection) -> bool {
     let filter_str = filter_text_lowercase.as_str();
 
     section.name.to_lowercase().contains(filter_str)
-}
\ No newline at end of file
+        || section.group().to_lowercase().contains(filter_str)
+}
+
+/// A row of the details table: either the heading of an area or a section in it.
+enum DetailRow<'a> {
+    Group(String),
+    Section(&'a GsxSection),
+}
+
+/// The sections that pass the filter in natural order, with a heading row before every area.
+/// Sorted by a column instead, there are no area headings.
+/// The map uses the same rows to scroll the table to a clicked stand.
+fn detail_rows<'a>(
+    sections: &'a [GsxSection],
+    filter_text: &str,
+    sort: Option<DetailSort>,
+) -> Vec<DetailRow<'a>> {
+    let sections = sections
+        .iter()
+        .filter(|&section| filter_profile_details(filter_text, section))
+        .sorted_by(|a, b| compare_stand_names(&a.name, &b.name));
+    if let Some(sort) = sort {
+        return sections
+            .sorted_by(|a, b| {
+                let ordering = sort.column.compare(a, b);
+                if sort.ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            })
+            .map(DetailRow::Section)
+            .collect();
+    }
+
+    let mut rows = vec![];
+    let mut current_group = None;
+    for section in sections {
+        let group = section.group();
+        if current_group.as_ref() != Some(&group) {
+            rows.push(DetailRow::Group(group.clone()));
+            current_group = Some(group);
+        }
+        rows.push(DetailRow::Section(section));
+    }
+    rows
+}
+
+/// A color for every area of the profile, assigned in the order of the details table.
+fn group_colors(sections: &[GsxSection]) -> HashMap<String, Color32> {
+    const GROUP_COLORS: [Color32; 8] = [
+        Color32::from_rgb(230, 25, 75),
+        Color32::from_rgb(60, 180, 75),
+        Color32::from_rgb(255, 225, 25),
+        Color32::from_rgb(0, 130, 200),
+        Color32::from_rgb(245, 130, 48),
+        Color32::from_rgb(145, 30, 180),
+        Color32::from_rgb(70, 240, 240),
+        Color32::from_rgb(240, 50, 230),
+    ];
+    detail_rows(sections, "", None)
+        .into_iter()
+        .filter_map(|row| match row {
+            DetailRow::Group(group) => Some(group),
+            DetailRow::Section(_) => None,
+        })
+        .enumerate()
+        .map(|(index, group)| (group, GROUP_COLORS[index % GROUP_COLORS.len()]))
+        .collect()
+}
+
+fn heading_text(heading: Option<f64>) -> String {
+    match heading {
+        Some(heading) => format!("{:.1}°", heading),
+        None => String::from("-"),
+    }
+}
+
+/// Color of stands that conflict with another stand, on the map and in the tables.
+const CONFLICT_COLOR: Color32 = Color32::from_rgb(255, 80, 80);
+
+/// Color of the pushback option at `index` of a section, the same in the table and on the map.
+fn pushback_color(index: usize) -> Color32 {
+    const PUSHBACK_COLORS: [Color32; 4] = [
+        Color32::from_rgb(255, 140, 0),
+        Color32::from_rgb(0, 200, 255),
+        Color32::from_rgb(120, 220, 60),
+        Color32::from_rgb(230, 80, 230),
+    ];
+    PUSHBACK_COLORS[index % PUSHBACK_COLORS.len()]
+}
@@ -1,10 +1,11 @@
 use egui::{
     text::{LayoutJob, LayoutSection, TextWrapping},
-    Color32, FontId, Pos2, Rect, Rounding, TextFormat, Vec2,
+    Align2, Color32, FontId, Pos2, Rect, Rounding, Shape, Stroke, TextFormat, Vec2,
 };
+use gsx_ini_parser::DeiceArea;
 use walkers::{extras::Place, Plugin, Position};
 
-use crate::core::ProfileFile;
+use crate::core::{GsxPosition, ProfileFile};
 
 pub struct ClickWatcher {
     pub places: Option<Vec<Place>>,
@@ -108,3 +109,199 @@ impl NoteDrawer {
         Self { selected_profile }
     }
 }
+
+/// Draws the name of every area of stands at its center, in the color of its stands.
+pub struct AreaLabelDrawer {
+    area_labels: Vec<(String, Color32, Position)>,
+}
+
+impl Plugin for AreaLabelDrawer {
+    fn run(
+        &mut self,
+        _response: &egui::Response,
+        painter: egui::Painter,
+        projector: &walkers::Projector,
+    ) {
+        for (label, color, position) in &self.area_labels {
+            let label_position = projector.project(*position).to_pos2();
+            let galley = painter.layout_no_wrap(label.to_owned(), FontId::proportional(16.0), *color);
+            let rect = Rect::from_center_size(label_position, galley.size());
+            painter.rect_filled(rect.expand(4.0), Rounding::from(5.0), Color32::BLACK.gamma_multiply(0.6));
+            painter.galley(rect.min, galley, *color);
+        }
+    }
+}
+
+impl AreaLabelDrawer {
+    pub fn new(area_labels: Vec<(String, Color32, Position)>) -> Self {
+        Self { area_labels }
+    }
+}
+
+/// Draws de-ice pads as squares of their real size, rotated to their heading.
+pub struct DeiceAreaDrawer {
+    deice_areas: Vec<DeiceArea>,
+}
+
+impl Plugin for DeiceAreaDrawer {
+    fn run(
+        &mut self,
+        _response: &egui::Response,
+        painter: egui::Painter,
+        projector: &walkers::Projector,
+    ) {
+        let color = Color32::from_rgb(0, 160, 255);
+        for deice_area in &self.deice_areas {
+            let half_size = deice_area.size / 2.0;
+            let (sin, cos) = deice_area.heading.to_radians().sin_cos();
+            let corners: Vec<Pos2> = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .iter()
+                .map(|(right, forward)| {
+                    // Offset in metres to the east and to the north, rotated to the heading
+                    let east = half_size * (right * cos + forward * sin);
+                    let north = half_size * (forward * cos - right * sin);
+                    let position = offset_position(deice_area.latitude, deice_area.longitude, east, north);
+                    projector.project(position).to_pos2()
+                })
+                .collect();
+            painter.add(Shape::convex_polygon(
+                corners,
+                color.gamma_multiply(0.3),
+                Stroke::new(1.5, color),
+            ));
+
+            let center = projector
+                .project(Position::from_lat_lon(deice_area.latitude, deice_area.longitude))
+                .to_pos2();
+            painter.text(
+                center,
+                Align2::CENTER_CENTER,
+                &deice_area.label,
+                FontId::proportional(11.0),
+                Color32::WHITE,
+            );
+        }
+    }
+}
+
+impl DeiceAreaDrawer {
+    pub fn new(deice_areas: Vec<DeiceArea>) -> Self {
+        Self { deice_areas }
+    }
+}
+
+/// Moves a position by the given metres. Precise enough for the size of an airport.
+fn offset_position(latitude: f64, longitude: f64, east: f64, north: f64) -> Position {
+    const METRES_PER_DEGREE: f64 = 111_320.0;
+    Position::from_lat_lon(
+        latitude + north / METRES_PER_DEGREE,
+        longitude + east / (METRES_PER_DEGREE * latitude.to_radians().cos()),
+    )
+}
+
+/// Draws the wingspan circles of stands, with the radius in metres.
+pub struct WingspanCircleDrawer {
+    circles: Vec<(Position, f64, Color32)>,
+}
+
+impl Plugin for WingspanCircleDrawer {
+    fn run(
+        &mut self,
+        _response: &egui::Response,
+        painter: egui::Painter,
+        projector: &walkers::Projector,
+    ) {
+        for (center, radius, color) in &self.circles {
+            let edge = offset_position(center.lat(), center.lon(), *radius, 0.0);
+            let center = projector.project(*center).to_pos2();
+            let radius = center.distance(projector.project(edge).to_pos2());
+            painter.circle(
+                center,
+                radius,
+                color.gamma_multiply(0.15),
+                Stroke::new(1.5, *color),
+            );
+        }
+    }
+}
+
+impl WingspanCircleDrawer {
+    pub fn new(circles: Vec<(Position, f64, Color32)>) -> Self {
+        Self { circles }
+    }
+}
+
+/// Draws pushback paths as lines from waypoint to waypoint, with an arrow in the middle of every
+/// leg showing the direction the tug drives.
+pub struct PushbackPathDrawer {
+    pushback_paths: Vec<(Vec<Position>, Color32)>,
+}
+
+impl Plugin for PushbackPathDrawer {
+    fn run(
+        &mut self,
+        _response: &egui::Response,
+        painter: egui::Painter,
+        projector: &walkers::Projector,
+    ) {
+        for (waypoints, color) in &self.pushback_paths {
+            let stroke = Stroke::new(2.5, *color);
+            let points: Vec<Pos2> = waypoints
+                .iter()
+                .map(|waypoint| projector.project(*waypoint).to_pos2())
+                .collect();
+            painter.add(Shape::line(points.clone(), stroke));
+
+            for leg in points.windows(2) {
+                let direction = (leg[1] - leg[0]).normalized();
+                if direction.any_nan() {
+                    continue;
+                }
+                let arrow_length = 12.0;
+                let middle = leg[0] + (leg[1] - leg[0]) / 2.0;
+                painter.arrow(middle - direction * (arrow_length / 2.0), direction * arrow_length, stroke);
+            }
+        }
+    }
+}
+
+impl PushbackPathDrawer {
+    pub fn new(pushback_paths: Vec<(Vec<Position>, Color32)>) -> Self {
+        Self { pushback_paths }
+    }
+}
+
+/// Draws a short line from a position in the direction of its heading, e.g. the way an aircraft
+/// faces after the pushback.
+pub struct HeadingTickDrawer {
+    headings: Vec<(GsxPosition, Color32)>,
+}
+
+impl Plugin for HeadingTickDrawer {
+    fn run(
+        &mut self,
+        _response: &egui::Response,
+        painter: egui::Painter,
+        projector: &walkers::Projector,
+    ) {
+        for (gsx_position, color) in &self.headings {
+            let Some(heading) = gsx_position.heading else {
+                continue;
+            };
+            let (sin, cos) = (heading as f32).to_radians().sin_cos();
+            // Screen y grows downwards, so north is -y
+            let direction = Vec2::new(sin, -cos);
+            let center = projector.project(gsx_position.position).to_pos2();
+            painter.line_segment(
+                [center + direction * 10.0, center + direction * 24.0],
+                Stroke::new(3.0, *color),
+            );
+        }
+    }
+}
+
+impl HeadingTickDrawer {
+    pub fn new(headings: Vec<(GsxPosition, Color32)>) -> Self {
+        Self { headings }
+    }
+}
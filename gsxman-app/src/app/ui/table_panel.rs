@@ -1,10 +1,22 @@
+use std::path::PathBuf;
+
 use egui::{Align, Color32, RichText, Ui};
 use egui_extras::{Column, Size, StripBuilder, TableBuilder};
+use gsx_ini_parser::{DeError, DeiceArea, MergeConflict, Resolution, Severity};
 use itertools::Itertools;
+use uuid::Uuid;
 
 use crate::app::GsxmanApp;
+use crate::core::{conflicts::Conflict, filehandling, settings::ProfileRootSource};
+
+use super::{
+    detail_columns::{DetailColumn, DetailSort},
+    detail_rows, filter_profiles, group_colors, heading_text, pushback_color, DetailRow, UIState,
+    CONFLICT_COLOR,
+};
 
-use super::{filter_profile_details, filter_profiles, UIState};
+/// Shown when no profile folder was found, e.g. on Linux without a Wine or Proton prefix of GSX.
+const NO_PROFILE_ROOTS_HINT: &str = "No GSX profile folder was found. Add the folder of your profiles under Profile Folders.";
 
 pub fn update_table_panel(app: &mut GsxmanApp, ui: &mut Ui) {
     if app.ui_state == UIState::Notes {
@@ -38,6 +50,10 @@ pub fn update_table_panel(app: &mut GsxmanApp, ui: &mut Ui) {
                             UIState::Overview => update_overview_table(app, ui),
                             UIState::Details => update_detail_table(app, ui),
                             UIState::SectionDetails => update_section_detail_table(app, ui),
+                            UIState::Problems => update_problem_table(app, ui),
+                            UIState::OrphanedNotes => update_orphaned_notes_table(app, ui),
+                            UIState::ProfileRoots => update_profile_root_table(app, ui),
+                            UIState::Merge => update_merge_table(app, ui),
                             _ => (),
                         });
                 });
@@ -45,6 +61,103 @@ pub fn update_table_panel(app: &mut GsxmanApp, ui: &mut Ui) {
     }
 }
 
+fn update_problem_table(app: &mut GsxmanApp, ui: &mut Ui) {
+    let selected_profile = app.get_selected_profile().unwrap();
+    ui.heading(format!(
+        "Problems in {} by {}",
+        selected_profile.file_name, selected_profile.creator
+    ));
+    ui.separator();
+    if selected_profile.problems.is_empty() {
+        ui.label("No problems found.");
+        return;
+    }
+
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Line
+        .column(Column::auto().clip(false)) //Severity
+        .column(Column::auto().clip(false)) //Section
+        .column(Column::auto().clip(false)) //Key
+        .column(Column::remainder().clip(false)) //Problem
+        .sense(egui::Sense::click());
+
+    let mut clicked_section_name = None;
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["Line", "Severity", "Section", "Key", "Problem"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            let filter_text = app.filter_text.to_lowercase();
+            for problem in selected_profile.problems.iter() {
+                let message = problem.kind.to_string();
+                let key = problem.key.clone().unwrap_or_default();
+                if ![&problem.section, &key, &message]
+                    .iter()
+                    .any(|text| text.to_lowercase().contains(&filter_text))
+                {
+                    continue;
+                }
+                let severity_color = match problem.severity {
+                    Severity::Error => Color32::RED,
+                    Severity::Warning => Color32::YELLOW,
+                };
+                body.row(30.0, |mut row| {
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(problem.line.to_string()).selectable(false));
+                    });
+                    row.col(|ui| {
+                        ui.add(
+                            egui::Label::new(
+                                RichText::new(problem.severity.to_string()).color(severity_color),
+                            )
+                            .selectable(false),
+                        );
+                    });
+                    for text in [problem.section.clone(), key, message] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false));
+                        });
+                    }
+
+                    if row.response().clicked() {
+                        clicked_section_name = Some(problem.section.clone());
+                    }
+                });
+            }
+        });
+
+    // Clicking a problem opens its stand in the details
+    if let Some(section_name) = clicked_section_name {
+        let sections = match &app.get_selected_profile().unwrap().profile_data {
+            Some(profile_data) => &profile_data.sections,
+            None => return,
+        };
+        let clicked_row = detail_rows(sections, "", app.detail_sort)
+            .iter()
+            .enumerate()
+            .find_map(|(row_index, detail_row)| match detail_row {
+                DetailRow::Section(section) if section.name == section_name => {
+                    Some((row_index, section.id))
+                }
+                _ => None,
+            });
+        if let Some((row_index, section_id)) = clicked_row {
+            app.selected_section_id = Some(section_id);
+            app.scroll_to_row = Some(row_index);
+            app.filter_text.clear();
+            app.ui_state = UIState::Details;
+        }
+    }
+}
+
 fn update_section_detail_table(app: &mut GsxmanApp, ui: &mut Ui) {
     let selected_profile = app.get_selected_profile().unwrap();
     let selected_section = app.get_selected_section().unwrap();
@@ -53,101 +166,320 @@ fn update_section_detail_table(app: &mut GsxmanApp, ui: &mut Ui) {
         selected_section.name, selected_profile.file_name
     ));
     ui.separator();
+    let attributes = selected_section.attributes();
+    if !attributes.is_empty() {
+        egui::CollapsingHeader::new("Attributes")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.push_id("attributes", |ui| {
+                    update_attribute_table(&attributes, &app.filter_text, ui);
+                });
+            });
+        ui.separator();
+    }
+    let mut clicked_section_id = None;
+    if !selected_section.conflicts.is_empty() {
+        egui::CollapsingHeader::new(
+            RichText::new(format!("Conflicts ({})", selected_section.conflicts.len()))
+                .color(CONFLICT_COLOR),
+        )
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.push_id("conflicts", |ui| {
+                clicked_section_id =
+                    update_conflict_table(&selected_section.conflicts, &app.filter_text, ui);
+            });
+        });
+        ui.separator();
+    }
     let table = TableBuilder::new(ui)
         .striped(true)
         .resizable(false)
         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
         .column(Column::auto().clip(false))
         .column(Column::auto().clip(false))
+        .column(Column::auto().clip(false))
         .column(Column::remainder().clip(false));
 
     table
         .header(20.0, |mut header| {
-            header.col(|ui| {
-                ui.add(egui::Label::new(RichText::new("Label").heading()).selectable(false));
-            });
-            header.col(|ui| {
-                ui.add(egui::Label::new(RichText::new("Latitude").heading()).selectable(false));
-            });
-            header.col(|ui| {
-                ui.add(egui::Label::new(RichText::new("Longitude").heading()).selectable(false));
-            });
+            for title in ["Label", "Latitude", "Longitude", "Heading"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
         })
         .body(|mut body| {
             let filter_text = app.filter_text.clone().to_lowercase();
             let filter_text_str = filter_text.as_str();
-            if let Some(pushback_position_left) = &selected_section.pushback_position_left {
-                if let Some(pushback_label_left) = &selected_section.pushback_label_left {
-                    if pushback_label_left.to_lowercase().contains(filter_text_str) {
-                        body.row(40.0, |mut row| {
-                            row.col(|ui| {
-                                ui.add(
-                                    egui::Label::new(pushback_label_left.to_owned())
-                                        .selectable(false),
-                                );
-                            });
-                            row.col(|ui| {
-                                ui.add(
-                                    egui::Label::new(pushback_position_left.lat().to_string())
-                                        .selectable(false),
-                                );
-                            });
-                            row.col(|ui| {
-                                ui.add(
-                                    egui::Label::new(pushback_position_left.lon().to_string())
-                                        .selectable(false),
-                                );
-                            });
+            let mut add_row = |label: RichText, latitude: String, longitude: String, heading: String| {
+                body.row(30.0, |mut row| {
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(label).selectable(false));
+                    });
+                    for text in [latitude, longitude, heading] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false));
                         });
                     }
+                });
+            };
+
+            for (index, pushback_path) in selected_section.pushback_paths.iter().enumerate() {
+                let label = pushback_path.label();
+                if !label.to_lowercase().contains(filter_text_str) {
+                    continue;
+                }
+                add_row(
+                    RichText::new(label).strong().color(pushback_color(index)),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                );
+                let waypoint_names = pushback_path
+                    .start
+                    .map(|_| String::from("Stand"))
+                    .into_iter()
+                    .chain((1..=pushback_path.approach.len()).map(|number| format!("Approach {}", number)))
+                    .chain([String::from("End")]);
+                for (name, waypoint) in waypoint_names.zip(pushback_path.waypoints()) {
+                    add_row(
+                        RichText::new(format!("    {}", name)),
+                        waypoint.position.lat().to_string(),
+                        waypoint.position.lon().to_string(),
+                        heading_text(waypoint.heading),
+                    );
                 }
             }
-            if let Some(pushback_position_right) = &selected_section.pushback_position_right {
-                if let Some(pushback_label_right) = &selected_section.pushback_label_right {
-                    if pushback_label_right
-                        .to_lowercase()
-                        .contains(filter_text_str)
-                    {
-                        body.row(40.0, |mut row| {
-                            row.col(|ui| {
-                                ui.add(
-                                    egui::Label::new(pushback_label_right.to_owned())
-                                        .selectable(false),
-                                );
-                            });
-                            row.col(|ui| {
-                                ui.add(
-                                    egui::Label::new(pushback_position_right.lat().to_string())
-                                        .selectable(false),
-                                );
-                            });
-                            row.col(|ui| {
-                                ui.add(
-                                    egui::Label::new(pushback_position_right.lon().to_string())
-                                        .selectable(false),
-                                );
-                            });
+            for (name, gsx_position) in selected_section.service_positions() {
+                if !name.to_lowercase().contains(filter_text_str) {
+                    continue;
+                }
+                add_row(
+                    RichText::new(name),
+                    gsx_position.position.lat().to_string(),
+                    gsx_position.position.lon().to_string(),
+                    heading_text(gsx_position.heading),
+                );
+            }
+            for error in selected_section.invalid_values.iter() {
+                let key = error.key.clone().unwrap_or_default();
+                if !key.to_lowercase().contains(filter_text_str) {
+                    continue;
+                }
+                add_row(
+                    RichText::new(key).color(Color32::RED),
+                    String::from("invalid value"),
+                    String::new(),
+                    String::new(),
+                );
+            }
+        });
+
+    // Clicking a conflict shows the other stand
+    if let Some(clicked_section_id) = clicked_section_id {
+        app.selected_section_id = Some(clicked_section_id);
+    }
+}
+
+fn update_invalid_value_table(invalid_values: &[&DeError], filter_text: &str, ui: &mut Ui) {
+    let filter_text = filter_text.to_lowercase();
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .vscroll(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Stand
+        .column(Column::auto().clip(false)) //Key
+        .column(Column::remainder().clip(false)); //Error
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["Stand", "Key", "Error"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            for error in invalid_values.iter().filter(|error| {
+                error
+                    .section
+                    .as_deref()
+                    .unwrap_or_default()
+                    .to_lowercase()
+                    .contains(&filter_text)
+            }) {
+                body.row(30.0, |mut row| {
+                    for text in [
+                        error.section.as_deref().unwrap_or_default(),
+                        error.key.as_deref().unwrap_or_default(),
+                        error.message.as_str(),
+                    ] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false));
                         });
                     }
-                }
+                });
             }
         });
 }
 
+/// Lists the conflicts of a stand and returns the other stand of the clicked one.
+fn update_conflict_table(conflicts: &[Conflict], filter_text: &str, ui: &mut Ui) -> Option<Uuid> {
+    let filter_text = filter_text.to_lowercase();
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .vscroll(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Stand
+        .column(Column::remainder().clip(false)) //Conflict
+        .sense(egui::Sense::click());
+
+    let mut clicked_section_id = None;
+    table
+        .header(20.0, |mut header| {
+            for title in ["Stand", "Conflict"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            for conflict in conflicts
+                .iter()
+                .filter(|conflict| conflict.to_string().to_lowercase().contains(&filter_text))
+            {
+                body.row(30.0, |mut row| {
+                    for text in [conflict.other_name.clone(), conflict.to_string()] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false));
+                        });
+                    }
+                    if row.response().clicked() {
+                        clicked_section_id = Some(conflict.other_section_id);
+                    }
+                });
+            }
+        });
+    clicked_section_id
+}
+
+fn update_attribute_table(attributes: &[(&'static str, String, String)], filter_text: &str, ui: &mut Ui) {
+    let filter_text = filter_text.to_lowercase();
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .vscroll(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Attribute
+        .column(Column::auto().clip(false)) //Value
+        .column(Column::remainder().clip(false)); //In profile
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["Attribute", "Value", "In profile"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            for (name, value, raw_value) in attributes
+                .iter()
+                .filter(|(name, _, _)| name.to_lowercase().contains(&filter_text))
+            {
+                body.row(30.0, |mut row| {
+                    for text in [*name, value.as_str(), raw_value.as_str()] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false));
+                        });
+                    }
+                });
+            }
+        });
+}
+
+
 fn update_detail_table(app: &mut GsxmanApp, ui: &mut Ui) {
     let selected_profile = app.get_selected_profile().unwrap();
-    ui.heading(format!(
+    let heading = format!(
         "Details {} by {}",
         selected_profile.file_name, selected_profile.creator
-    ));
+    );
+    ui.horizontal(|ui| {
+        ui.heading(heading);
+        ui.menu_button("Columns", |ui| {
+            for column in DetailColumn::ALL {
+                let mut visible = app.detail_columns.contains(&column);
+                if ui.checkbox(&mut visible, column.title()).changed() {
+                    // Keep the columns in their usual order, whichever is turned on first
+                    app.detail_columns = DetailColumn::ALL
+                        .into_iter()
+                        .filter(|&other| {
+                            if other == column {
+                                visible
+                            } else {
+                                app.detail_columns.contains(&other)
+                            }
+                        })
+                        .collect();
+                    if !visible && app.detail_sort.map(|sort| sort.column) == Some(column) {
+                        app.detail_sort = None;
+                    }
+                }
+            }
+        });
+    });
     ui.separator();
+    let selected_profile = app.get_selected_profile().unwrap();
+    let invalid_values: Vec<&DeError> = selected_profile
+        .profile_data
+        .as_ref()
+        .unwrap()
+        .sections
+        .iter()
+        .flat_map(|section| section.invalid_values.iter())
+        .collect();
+    if !invalid_values.is_empty() {
+        egui::CollapsingHeader::new(
+            RichText::new(format!("Invalid values ({})", invalid_values.len()))
+                .color(Color32::RED),
+        )
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.push_id("invalid_values", |ui| {
+                update_invalid_value_table(&invalid_values, &app.filter_text, ui);
+            });
+        });
+        ui.separator();
+    }
+    let deice_areas = &selected_profile.profile_data.as_ref().unwrap().deice_areas;
+    if !deice_areas.is_empty() {
+        egui::CollapsingHeader::new(format!("De-ice areas ({})", deice_areas.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.push_id("deice_areas", |ui| {
+                    update_deice_area_table(deice_areas, &app.filter_text, ui);
+                });
+            });
+        ui.separator();
+    }
     let mut table = TableBuilder::new(ui)
         .striped(true)
         .resizable(false)
-        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-        .column(Column::auto().clip(false))
-        .column(Column::auto().clip(false))
-        .column(Column::remainder().clip(false));
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+    // Locationname and the chosen columns, the last one takes the remaining width
+    let column_count = app.detail_columns.len() + 1;
+    for index in 0..column_count {
+        let column = if index + 1 == column_count {
+            Column::remainder()
+        } else {
+            Column::auto()
+        };
+        table = table.column(column.clip(false));
+    }
 
     table = table.sense(egui::Sense::click());
 
@@ -156,50 +488,94 @@ fn update_detail_table(app: &mut GsxmanApp, ui: &mut Ui) {
     }
 
     let mut clicked_section_id = None;
+    // Clicking a header sorts by it, ascending, then descending, then back to the areas
+    let mut clicked_column = None;
 
     table
         .header(20.0, |mut header| {
             header.col(|ui| {
-                ui.add(egui::Label::new(RichText::new("Locationname").heading()).selectable(false));
-            });
-            header.col(|ui| {
-                ui.add(egui::Label::new(RichText::new("Latitude").heading()).selectable(false));
-            });
-            header.col(|ui| {
-                ui.add(egui::Label::new(RichText::new("Longitude").heading()).selectable(false));
+                if sort_header(ui, "Locationname", None).clicked() {
+                    clicked_column = Some(None);
+                }
             });
+            for &column in app.detail_columns.iter() {
+                header.col(|ui| {
+                    let ascending = app
+                        .detail_sort
+                        .filter(|sort| sort.column == column)
+                        .map(|sort| sort.ascending);
+                    if sort_header(ui, column.title(), ascending).clicked() {
+                        clicked_column = Some(Some(column));
+                    }
+                });
+            }
         })
         .body(|mut body| {
-            let sections = selected_profile
-                .profile_data
-                .as_ref()
-                .unwrap()
-                .sections
-                .clone();
-            let filter_text = app.filter_text.clone();
-            let sections_iter = sections
-                .iter()
-                .sorted_by(|a, b| Ord::cmp(&a.name, &b.name))
-                .filter(|&section| filter_profile_details(&filter_text, section));
-            for section in sections_iter {
+            let sections = &selected_profile.profile_data.as_ref().unwrap().sections;
+            let group_colors = group_colors(sections);
+            for detail_row in detail_rows(sections, &app.filter_text, app.detail_sort) {
+                let section = match detail_row {
+                    DetailRow::Group(group) => {
+                        body.row(30.0, |mut row| {
+                            row.col(|ui| {
+                                ui.add(
+                                    egui::Label::new(
+                                        RichText::new(group.as_str())
+                                            .strong()
+                                            .color(group_colors[&group]),
+                                    )
+                                    .selectable(false),
+                                );
+                            });
+                            for _ in 0..app.detail_columns.len() {
+                                row.col(|_| {});
+                            }
+                        });
+                        continue;
+                    }
+                    DetailRow::Section(section) => section,
+                };
                 body.row(40.0, |mut row| {
                     if let Some(selected_section_id) = app.selected_section_id.as_ref() {
                         row.set_selected(*selected_section_id == section.id);
                     }
 
                     row.col(|ui| {
-                        ui.add(egui::Label::new(section.name.to_string()).selectable(false));
-                    });
-                    row.col(|ui| {
-                        ui.add(
-                            egui::Label::new(section.position.lat().to_string()).selectable(false),
-                        );
-                    });
-                    row.col(|ui| {
-                        ui.add(
-                            egui::Label::new(section.position.lon().to_string()).selectable(false),
-                        );
+                        if section.conflicts.is_empty() {
+                            ui.add(egui::Label::new(section.name.to_string()).selectable(false));
+                        } else {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    RichText::new(format!("⚠ {}", section.name))
+                                        .color(CONFLICT_COLOR),
+                                )
+                                .selectable(false),
+                            );
+                            response.on_hover_text(
+                                section.conflicts.iter().map(|conflict| conflict.to_string()).join("\n"),
+                            );
+                        }
+                        if !section.invalid_values.is_empty() {
+                            let response = ui.add(
+                                egui::Label::new(RichText::new("invalid value").color(Color32::RED))
+                                    .selectable(false),
+                            );
+                            response.on_hover_text(
+                                section.invalid_values.iter().map(|error| error.to_string()).join("\n"),
+                            );
+                        }
                     });
+                    // Readable values, with the value as it is written in the profile on hover
+                    for column in app.detail_columns.iter() {
+                        row.col(|ui| {
+                            if let Some((value, raw_value)) = column.value(section) {
+                                let response = ui.add(egui::Label::new(value).selectable(false));
+                                if let Some(raw_value) = raw_value {
+                                    response.on_hover_text(raw_value);
+                                }
+                            }
+                        });
+                    }
 
                     if row.response().clicked() {
                         clicked_section_id = Some(section.id);
@@ -208,6 +584,26 @@ fn update_detail_table(app: &mut GsxmanApp, ui: &mut Ui) {
             }
         });
 
+    if let Some(column) = clicked_column {
+        app.detail_sort = match (column, app.detail_sort) {
+            (None, _) => None,
+            (Some(column), Some(sort)) if sort.column == column => {
+                if sort.ascending {
+                    Some(DetailSort {
+                        column,
+                        ascending: false,
+                    })
+                } else {
+                    None
+                }
+            }
+            (Some(column), _) => Some(DetailSort {
+                column,
+                ascending: true,
+            }),
+        };
+    }
+
     if let Some(clicked_section_id) = clicked_section_id {
         if let Some(selected_section_id) = app.selected_section_id.as_ref() {
             if *selected_section_id == clicked_section_id {
@@ -222,8 +618,76 @@ fn update_detail_table(app: &mut GsxmanApp, ui: &mut Ui) {
     app.scroll_to_row = None;
 }
 
+fn update_deice_area_table(deice_areas: &[DeiceArea], filter_text: &str, ui: &mut Ui) {
+    let filter_text = filter_text.to_lowercase();
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .vscroll(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Label
+        .column(Column::auto().clip(false)) //Latitude
+        .column(Column::auto().clip(false)) //Longitude
+        .column(Column::auto().clip(false)) //Heading
+        .column(Column::auto().clip(false)) //Size
+        .column(Column::remainder().clip(false)); //Object
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["Label", "Latitude", "Longitude", "Heading", "Size", "Object"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            for deice_area in deice_areas
+                .iter()
+                .filter(|deice_area| deice_area.label.to_lowercase().contains(&filter_text))
+            {
+                body.row(30.0, |mut row| {
+                    for text in [
+                        deice_area.label.to_owned(),
+                        deice_area.latitude.to_string(),
+                        deice_area.longitude.to_string(),
+                        format!("{}°", deice_area.heading),
+                        format!("{} m", deice_area.size),
+                        deice_area.object_type.clone().unwrap_or_default(),
+                    ] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false));
+                        });
+                    }
+                });
+            }
+        });
+}
+
+/// A clickable column header, with an arrow if the table is sorted by it.
+fn sort_header(ui: &mut Ui, title: &str, ascending: Option<bool>) -> egui::Response {
+    let title = match ascending {
+        Some(true) => format!("{} ⏶", title),
+        Some(false) => format!("{} ⏷", title),
+        None => String::from(title),
+    };
+    ui.add(
+        egui::Label::new(RichText::new(title).heading())
+            .selectable(false)
+            .sense(egui::Sense::click()),
+    )
+}
+
 fn update_overview_table(app: &mut GsxmanApp, ui: &mut Ui) {
-    ui.heading("Installed GSX Profiles");
+    ui.horizontal(|ui| {
+        ui.heading("Installed GSX Profiles");
+        if app.profile_roots.len() > 1 {
+            ui.add_space(20.0);
+            update_root_filter(app, ui);
+        }
+    });
+    if app.profile_roots.is_empty() {
+        ui.label(NO_PROFILE_ROOTS_HINT);
+    }
     ui.separator();
     let mut table = TableBuilder::new(ui)
         .striped(true)
@@ -231,6 +695,7 @@ fn update_overview_table(app: &mut GsxmanApp, ui: &mut Ui) {
         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
         .column(Column::auto().clip(false)) //ICAO
         .column(Column::auto().clip(true)) //Airport Name
+        .column(Column::auto().clip(true)) //Root
         .column(Column::initial(400.0).clip(true)) //File Location
         .column(Column::auto().clip(false)) //Creator
         .column(Column::remainder().clip(false)); //Last Modified
@@ -249,6 +714,9 @@ fn update_overview_table(app: &mut GsxmanApp, ui: &mut Ui) {
             header.col(|ui| {
                 ui.add(egui::Label::new(RichText::new("Airport Name").heading()).selectable(false));
             });
+            header.col(|ui| {
+                ui.add(egui::Label::new(RichText::new("Root").heading()).selectable(false));
+            });
             header.col(|ui| {
                 ui.add(
                     egui::Label::new(RichText::new("File Location").heading()).selectable(false),
@@ -263,11 +731,14 @@ fn update_overview_table(app: &mut GsxmanApp, ui: &mut Ui) {
         })
         .body(|mut body| {
             let filter_text = app.filter_text.clone();
+            let root_filter = app.root_filter.clone();
             let installed_profiles = app.installed_gsx_profiles.clone();
             let installed_profiles_iter = installed_profiles
                 .iter()
                 .sorted_by(|a, b| Ord::cmp(&a.1.airport.icao, &b.1.airport.icao))
-                .filter(|&(_, profile)| filter_profiles(&filter_text, profile));
+                .filter(|&(_, profile)| {
+                    filter_profiles(&filter_text, root_filter.as_deref(), profile)
+                });
 
             for (id, profile) in installed_profiles_iter {
                 body.row(40.0, |mut row| {
@@ -290,12 +761,66 @@ fn update_overview_table(app: &mut GsxmanApp, ui: &mut Ui) {
                         if profile.has_duplicate_error && response.hovered() {
                             response.on_hover_text("There is a duplicate Profile of the Same airport. Consider deleting one of them.");
                         }
+                        if !profile.problems.is_empty() {
+                            let problems_count = profile.problems.len();
+                            let has_errors = profile
+                                .problems
+                                .iter()
+                                .any(|problem| problem.severity == Severity::Error);
+                            let badge = if has_errors {
+                                RichText::new(format!("❌ {}", problems_count)).color(Color32::RED)
+                            } else {
+                                RichText::new(format!("⚠ {}", problems_count))
+                                    .color(Color32::from_rgb(255, 140, 0))
+                            };
+                            let response = ui.add(egui::Label::new(badge).selectable(false));
+                            if response.hovered() {
+                                let mut hover_text = format!(
+                                    "This profile has {} problem(s), see Profile Problems:",
+                                    problems_count
+                                );
+                                for problem in profile.problems.iter().take(10) {
+                                    hover_text += format!("\nLine {}", problem).as_str();
+                                }
+                                if problems_count > 10 {
+                                    hover_text += format!("\n... and {} more", problems_count - 10).as_str();
+                                }
+                                response.on_hover_text(hover_text);
+                            }
+                        }
+                        if !profile.parse_diagnostics.is_empty() {
+                            let diagnostics_count = profile.parse_diagnostics.len();
+                            let response = ui.add(
+                                egui::Label::new(
+                                    RichText::new(format!("⚠ {}", diagnostics_count))
+                                        .color(Color32::YELLOW),
+                                )
+                                .selectable(false),
+                            );
+                            if response.hovered() {
+                                let mut hover_text = format!(
+                                    "This profile has {} line(s) GSX may misread:",
+                                    diagnostics_count
+                                );
+                                for diagnostic in profile.parse_diagnostics.iter().take(10) {
+                                    hover_text += format!("\nLine {}", diagnostic).as_str();
+                                }
+                                if diagnostics_count > 10 {
+                                    hover_text += format!("\n... and {} more", diagnostics_count - 10).as_str();
+                                }
+                                response.on_hover_text(hover_text);
+                            }
+                        }
                     });
                     row.col(|ui| {
                         ui.add(
                             egui::Label::new(profile.airport.name.to_string()).selectable(false),
                         );
                     });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(&profile.root.label).selectable(false))
+                            .on_hover_text(profile.root.path.display().to_string());
+                    });
                     row.col(|ui| {
                         ui.add(
                             egui::Label::new(profile.file_location.as_os_str().to_str().unwrap())
@@ -332,6 +857,319 @@ fn update_overview_table(app: &mut GsxmanApp, ui: &mut Ui) {
     app.scroll_to_row = None;
 }
 
+fn update_merge_table(app: &mut GsxmanApp, ui: &mut Ui) {
+    let filter_text = app.filter_text.to_lowercase();
+    let Some(pending_merge) = app.pending_merge.as_mut() else {
+        return;
+    };
+    ui.heading(format!(
+        "Merge {} into {}",
+        pending_merge.upstream_file.file_name().unwrap().to_str().unwrap(),
+        pending_merge.installed_file.file_name().unwrap().to_str().unwrap()
+    ));
+    ui.label("Changes that were only made on one side are already merged. Choose which version to keep for every conflict.");
+    ui.separator();
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Section
+        .column(Column::auto().clip(false)) //Key
+        .column(Column::initial(150.0).clip(true)) //Original
+        .column(Column::initial(150.0).clip(true)) //Installed
+        .column(Column::initial(150.0).clip(true)) //Update
+        .column(Column::remainder().clip(false)); //Keep
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["Section", "Key", "Original", "Installed", "Update", "Keep"] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            let conflicts = pending_merge.result.conflicts.iter();
+            for (conflict, resolution) in conflicts.zip(pending_merge.resolutions.iter_mut()) {
+                if !conflict.section().to_lowercase().contains(&filter_text) {
+                    continue;
+                }
+                let (key, original, installed, update) = match conflict {
+                    MergeConflict::Key {
+                        key,
+                        base,
+                        local,
+                        upstream,
+                        ..
+                    } => (
+                        key.as_str(),
+                        base.as_deref().unwrap_or("(not set)"),
+                        local.as_deref().unwrap_or("(not set)"),
+                        upstream.as_deref().unwrap_or("(not set)"),
+                    ),
+                    MergeConflict::RemovedLocally { .. } => {
+                        ("(whole section)", "", "(removed)", "(changed)")
+                    }
+                    MergeConflict::RemovedUpstream { .. } => {
+                        ("(whole section)", "", "(changed)", "(removed)")
+                    }
+                };
+                body.row(40.0, |mut row| {
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(conflict.section()).selectable(false));
+                    });
+                    for text in [key, original, installed, update] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(text).selectable(false))
+                                .on_hover_text(text);
+                        });
+                    }
+                    row.col(|ui| {
+                        ui.radio_value(resolution, Resolution::Local, "Installed");
+                        ui.radio_value(resolution, Resolution::Upstream, "Update");
+                    });
+                });
+            }
+        });
+}
+
+fn update_root_filter(app: &mut GsxmanApp, ui: &mut Ui) {
+    let selected_text = app
+        .profile_roots
+        .iter()
+        .find(|root| Some(&root.path) == app.root_filter.as_ref())
+        .map_or("All folders", |root| root.label.as_str());
+    egui::ComboBox::from_id_source("root_filter")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut app.root_filter, None, "All folders");
+            for root in app.profile_roots.iter() {
+                ui.selectable_value(&mut app.root_filter, Some(root.path.clone()), &root.label)
+                    .on_hover_text(root.path.display().to_string());
+            }
+        });
+}
+
+/// The folders profiles are read from. Labels of added folders can be changed, detected ones are
+/// shown as they are.
+fn update_profile_root_table(app: &mut GsxmanApp, ui: &mut Ui) {
+    ui.heading("Profile Folders");
+    if app
+        .profile_roots
+        .iter()
+        .any(|root| root.source == ProfileRootSource::Override)
+    {
+        ui.label("The folders were given on the command line or in GSXMAN_PROFILE_PATH, so only they are used.");
+    }
+    if app.profile_roots.is_empty() {
+        ui.label(NO_PROFILE_ROOTS_HINT);
+    }
+    ui.separator();
+
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //Label
+        .column(Column::remainder().clip(true)) //Folder
+        .column(Column::auto().clip(false)) //Source
+        .column(Column::auto().clip(false)) //Profiles
+        .column(Column::auto().clip(false)); //Actions
+
+    let mut changed_label: Option<(PathBuf, String)> = None;
+    let mut label_done = false;
+    let mut removed: Option<PathBuf> = None;
+    let filter_text = app.filter_text.to_lowercase();
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["Label", "Folder", "Source", "Profiles", ""] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            for root in app.profile_roots.iter() {
+                let folder = root.path.display().to_string();
+                if ![&root.label, &folder]
+                    .iter()
+                    .any(|text| text.to_lowercase().contains(&filter_text))
+                {
+                    continue;
+                }
+                let profile_count = app
+                    .installed_gsx_profiles
+                    .values()
+                    .filter(|profile| profile.root.path == root.path)
+                    .count();
+                body.row(30.0, |mut row| {
+                    row.col(|ui| {
+                        if root.source == ProfileRootSource::User {
+                            let mut label = root.label.clone();
+                            let response = ui.add(egui::TextEdit::singleline(&mut label).desired_width(150.0));
+                            if response.changed() {
+                                changed_label = Some((root.path.clone(), label));
+                            }
+                            label_done |= response.lost_focus();
+                        } else {
+                            ui.add(egui::Label::new(&root.label).selectable(false));
+                        }
+                    });
+                    row.col(|ui| {
+                        if root.path.is_dir() {
+                            ui.add(egui::Label::new(&folder).selectable(false));
+                        } else {
+                            ui.add(egui::Label::new(RichText::new(&folder).color(Color32::RED)).selectable(false))
+                                .on_hover_text("The folder doesn't exist");
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(root.source.to_string()).selectable(false));
+                    });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(profile_count.to_string()).selectable(false));
+                    });
+                    row.col(|ui| {
+                        if root.source == ProfileRootSource::User && ui.button("Remove").clicked() {
+                            removed = Some(root.path.clone());
+                        }
+                    });
+                });
+            }
+        });
+
+    // Labels are shown as they are typed and stored once the field is left, the profiles don't
+    // need to be read again for it
+    if let Some((path, label)) = changed_label {
+        for root in app
+            .user_data
+            .settings
+            .profile_roots
+            .iter_mut()
+            .chain(app.profile_roots.iter_mut())
+            .filter(|root| root.path == path)
+        {
+            root.label = label.clone();
+        }
+        for profile in app.installed_gsx_profiles.values_mut() {
+            if profile.root.path == path {
+                profile.root.label = label.clone();
+            }
+        }
+    }
+    if label_done {
+        filehandling::write_user_data(&app.user_data);
+    }
+    if let Some(path) = removed {
+        app.user_data
+            .settings
+            .profile_roots
+            .retain(|root| root.path != path);
+        filehandling::write_user_data(&app.user_data);
+        app.reload_profile_roots();
+    }
+}
+
+/// Notes whose profile can't be found anymore, to give them to another profile or delete them.
+fn update_orphaned_notes_table(app: &mut GsxmanApp, ui: &mut Ui) {
+    ui.heading("Orphaned Notes");
+    ui.label("Notes of profiles that were removed, or changed too much to be recognized.");
+    ui.separator();
+
+    let orphans = app
+        .user_data
+        .orphaned_profiles(app.installed_gsx_profiles.values());
+    if orphans.is_empty() {
+        ui.label("No orphaned notes.");
+        return;
+    }
+
+    let table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(false)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().clip(false)) //ICAO
+        .column(Column::auto().clip(false)) //Creator
+        .column(Column::auto().clip(false)) //File
+        .column(Column::remainder().clip(true)) //Notes
+        .column(Column::auto().clip(false)); //Actions
+
+    // Reassigning or deleting changes the indexes, so it's done after the table
+    let mut reassign: Option<(usize, Uuid)> = None;
+    let mut delete: Option<usize> = None;
+
+    table
+        .header(20.0, |mut header| {
+            for title in ["ICAO", "Creator", "File", "Notes", ""] {
+                header.col(|ui| {
+                    ui.add(egui::Label::new(RichText::new(title).heading()).selectable(false));
+                });
+            }
+        })
+        .body(|mut body| {
+            let filter_text = app.filter_text.to_lowercase();
+            for index in orphans {
+                let orphan = &app.user_data.profiles[index];
+                let file_name = std::path::Path::new(&orphan.path)
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                if ![&orphan.identity.icao, &orphan.identity.creator, &file_name, &orphan.notes]
+                    .iter()
+                    .any(|text| text.to_lowercase().contains(&filter_text))
+                {
+                    continue;
+                }
+                body.row(30.0, |mut row| {
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(&orphan.identity.icao).selectable(false));
+                    });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(&orphan.identity.creator).selectable(false));
+                    });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(&file_name).selectable(false))
+                            .on_hover_text(&orphan.path);
+                    });
+                    row.col(|ui| {
+                        let first_line = orphan.notes.lines().next().unwrap_or_default();
+                        ui.add(egui::Label::new(first_line).selectable(false))
+                            .on_hover_text(&orphan.notes);
+                    });
+                    row.col(|ui| {
+                        egui::ComboBox::from_id_source(("reassign_orphan", index))
+                            .selected_text("Reassign to")
+                            .show_ui(ui, |ui| {
+                                // Profiles of the same airport first
+                                let profiles = app.installed_gsx_profiles.values().sorted_by_key(
+                                    |profile| (profile.identity.icao != orphan.identity.icao, &profile.file_name),
+                                );
+                                for profile in profiles {
+                                    if ui.selectable_label(false, &profile.file_name).clicked() {
+                                        reassign = Some((index, profile.id));
+                                    }
+                                }
+                            });
+                        if ui.button("Delete").clicked() {
+                            delete = Some(index);
+                        }
+                    });
+                });
+            }
+        });
+
+    if let Some((index, profile_id)) = reassign {
+        if let Some(profile) = app.installed_gsx_profiles.get(&profile_id) {
+            app.user_data.reassign(index, profile);
+            filehandling::write_user_data(&app.user_data);
+        }
+    } else if let Some(index) = delete {
+        app.user_data.profiles.remove(index);
+        filehandling::write_user_data(&app.user_data);
+    }
+}
+
 fn update_notes(app: &mut GsxmanApp, ui: &mut Ui) {
     if let Some(selected_profile) = app.get_selected_profile_mut() {
         let mut size = ui.available_size();
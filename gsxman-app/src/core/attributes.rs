@@ -0,0 +1,344 @@
+use std::fmt;
+
+// Readable versions of the codes GSX writes for a stand. Every type keeps values it doesn't know
+// in `Unknown`, so `from_value(value).to_value()` gives back what was in the profile.
+
+/// `type`, the parking category of the scenery (the MSFS parking type).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParkingType {
+    None,
+    RampGa,
+    RampGaSmall,
+    RampGaMedium,
+    RampGaLarge,
+    RampCargo,
+    RampMilitaryCargo,
+    RampMilitaryCombat,
+    GateSmall,
+    GateMedium,
+    GateHeavy,
+    DockGa,
+    Fuel,
+    Vehicles,
+    RampGaExtra,
+    GateExtra,
+    Unknown(String),
+}
+
+/// `pushback`, the directions the tug may push to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushbackDirections {
+    None,
+    Left,
+    Right,
+    LeftAndRight,
+    Unknown(String),
+}
+
+/// One entry of `loadertype`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoaderType {
+    None,
+    BeltLoader,
+    UldLoader,
+    BeltAndUldLoader,
+    Unknown(String),
+}
+
+/// `walkertype`, how passengers get to the aircraft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalkerType {
+    JetwayPassenger,
+    Unknown(String),
+}
+
+/// `parkingsystem`, what guides the aircraft into the stand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParkingSystem {
+    None,
+    Marshaller,
+    /// A Safedock docking guidance system, with the model GSX places, e.g. `TS24Grey`.
+    SafeDock(String),
+    Unknown(String),
+}
+
+/// A `0`/`1` setting like `hasjetway`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flag {
+    No,
+    Yes,
+    Unknown(String),
+}
+
+const PARKING_TYPES: [(&str, ParkingType, &str); 16] = [
+    ("0", ParkingType::None, "None"),
+    ("1", ParkingType::RampGa, "Ramp GA"),
+    ("2", ParkingType::RampGaSmall, "Ramp GA small"),
+    ("3", ParkingType::RampGaMedium, "Ramp GA medium"),
+    ("4", ParkingType::RampGaLarge, "Ramp GA large"),
+    ("5", ParkingType::RampCargo, "Ramp cargo"),
+    ("6", ParkingType::RampMilitaryCargo, "Ramp military cargo"),
+    ("7", ParkingType::RampMilitaryCombat, "Ramp military combat"),
+    ("8", ParkingType::GateSmall, "Gate small"),
+    ("9", ParkingType::GateMedium, "Gate medium"),
+    ("10", ParkingType::GateHeavy, "Gate heavy"),
+    ("11", ParkingType::DockGa, "Dock GA"),
+    ("12", ParkingType::Fuel, "Fuel"),
+    ("13", ParkingType::Vehicles, "Vehicles"),
+    ("14", ParkingType::RampGaExtra, "Ramp GA extra"),
+    ("15", ParkingType::GateExtra, "Gate extra"),
+];
+
+const PUSHBACK_DIRECTIONS: [(&str, PushbackDirections, &str); 4] = [
+    ("0", PushbackDirections::None, "No pushback"),
+    ("1", PushbackDirections::Left, "Left only"),
+    ("2", PushbackDirections::Right, "Right only"),
+    ("3", PushbackDirections::LeftAndRight, "Left and right"),
+];
+
+const LOADER_TYPES: [(&str, LoaderType, &str); 4] = [
+    ("0", LoaderType::None, "None"),
+    ("1", LoaderType::BeltLoader, "Belt loader"),
+    ("2", LoaderType::UldLoader, "ULD loader"),
+    ("3", LoaderType::BeltAndUldLoader, "Belt and ULD loader"),
+];
+
+const SAFEDOCK_PREFIX: &str = "SafeDock";
+
+/// Looks up a value in a table of `(value, variant, name)`.
+fn decode<T: Clone>(table: &[(&str, T, &str)], value: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(table_value, _, _)| *table_value == value.trim())
+        .map(|(_, variant, _)| variant.clone())
+}
+
+fn entry<'a, T: PartialEq>(table: &'a [(&'a str, T, &'a str)], variant: &T) -> (&'a str, &'a str) {
+    table
+        .iter()
+        .find(|(_, table_variant, _)| table_variant == variant)
+        .map(|(value, _, name)| (*value, *name))
+        .unwrap()
+}
+
+impl ParkingType {
+    pub fn from_value(value: &str) -> Self {
+        decode(&PARKING_TYPES, value).unwrap_or_else(|| ParkingType::Unknown(String::from(value)))
+    }
+
+    pub fn to_value(&self) -> String {
+        match self {
+            ParkingType::Unknown(value) => value.clone(),
+            known => String::from(entry(&PARKING_TYPES, known).0),
+        }
+    }
+}
+
+impl PushbackDirections {
+    pub fn from_value(value: &str) -> Self {
+        decode(&PUSHBACK_DIRECTIONS, value)
+            .unwrap_or_else(|| PushbackDirections::Unknown(String::from(value)))
+    }
+
+    pub fn to_value(&self) -> String {
+        match self {
+            PushbackDirections::Unknown(value) => value.clone(),
+            known => String::from(entry(&PUSHBACK_DIRECTIONS, known).0),
+        }
+    }
+}
+
+impl LoaderType {
+    /// Decodes every entry of a `loadertype` value like `3 3 2`.
+    pub fn from_list(value: &str) -> Vec<Self> {
+        value.split_whitespace().map(LoaderType::from_value).collect()
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        decode(&LOADER_TYPES, value).unwrap_or_else(|| LoaderType::Unknown(String::from(value)))
+    }
+
+    pub fn to_value(&self) -> String {
+        match self {
+            LoaderType::Unknown(value) => value.clone(),
+            known => String::from(entry(&LOADER_TYPES, known).0),
+        }
+    }
+}
+
+impl WalkerType {
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "GsxJetwayPassenger" => WalkerType::JetwayPassenger,
+            _ => WalkerType::Unknown(String::from(value)),
+        }
+    }
+
+    pub fn to_value(&self) -> String {
+        match self {
+            WalkerType::JetwayPassenger => String::from("GsxJetwayPassenger"),
+            WalkerType::Unknown(value) => value.clone(),
+        }
+    }
+}
+
+impl ParkingSystem {
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "" => ParkingSystem::None,
+            "Marshaller" => ParkingSystem::Marshaller,
+            _ => match value.strip_prefix(SAFEDOCK_PREFIX) {
+                Some(model) if !model.is_empty() => ParkingSystem::SafeDock(String::from(model)),
+                _ => ParkingSystem::Unknown(String::from(value)),
+            },
+        }
+    }
+
+    pub fn to_value(&self) -> String {
+        match self {
+            ParkingSystem::None => String::new(),
+            ParkingSystem::Marshaller => String::from("Marshaller"),
+            ParkingSystem::SafeDock(model) => format!("{}{}", SAFEDOCK_PREFIX, model),
+            ParkingSystem::Unknown(value) => value.clone(),
+        }
+    }
+}
+
+impl Flag {
+    pub fn from_value(value: &str) -> Self {
+        match value.trim() {
+            "0" => Flag::No,
+            "1" => Flag::Yes,
+            _ => Flag::Unknown(String::from(value)),
+        }
+    }
+
+    pub fn to_value(&self) -> String {
+        match self {
+            Flag::No => String::from("0"),
+            Flag::Yes => String::from("1"),
+            Flag::Unknown(value) => value.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ParkingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParkingType::Unknown(value) => write!(f, "Unknown ({})", value),
+            known => f.write_str(entry(&PARKING_TYPES, known).1),
+        }
+    }
+}
+
+impl fmt::Display for PushbackDirections {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushbackDirections::Unknown(value) => write!(f, "Unknown ({})", value),
+            known => f.write_str(entry(&PUSHBACK_DIRECTIONS, known).1),
+        }
+    }
+}
+
+impl fmt::Display for LoaderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoaderType::Unknown(value) => write!(f, "Unknown ({})", value),
+            known => f.write_str(entry(&LOADER_TYPES, known).1),
+        }
+    }
+}
+
+impl fmt::Display for WalkerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalkerType::JetwayPassenger => f.write_str("Jetway passengers"),
+            WalkerType::Unknown(value) => f.write_str(value),
+        }
+    }
+}
+
+impl fmt::Display for ParkingSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParkingSystem::None => f.write_str("None"),
+            ParkingSystem::Marshaller => f.write_str("Marshaller"),
+            ParkingSystem::SafeDock(model) => write!(f, "Safedock ({})", model),
+            ParkingSystem::Unknown(value) => f.write_str(value),
+        }
+    }
+}
+
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Flag::No => f.write_str("No"),
+            Flag::Yes => f.write_str("Yes"),
+            Flag::Unknown(value) => write!(f, "Unknown ({})", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parking_types_round_trip() {
+        assert_eq!(ParkingType::from_value("9"), ParkingType::GateMedium);
+        assert_eq!(ParkingType::from_value("9").to_string(), "Gate medium");
+        for value in ["0", "9", "15", "09", "16", "-1", "gate", ""] {
+            assert_eq!(ParkingType::from_value(value).to_value(), value);
+        }
+        assert_eq!(ParkingType::from_value("09"), ParkingType::Unknown(String::from("09")));
+    }
+
+    #[test]
+    fn pushback_directions_round_trip() {
+        assert_eq!(PushbackDirections::from_value("3"), PushbackDirections::LeftAndRight);
+        for value in ["0", "1", "2", "3", "03", "4", "left", "1.5"] {
+            assert_eq!(PushbackDirections::from_value(value).to_value(), value);
+        }
+        assert_eq!(PushbackDirections::from_value("4").to_string(), "Unknown (4)");
+    }
+
+    #[test]
+    fn loader_types_round_trip() {
+        let loader_types = LoaderType::from_list("3 3 2");
+        assert_eq!(
+            loader_types,
+            vec![
+                LoaderType::BeltAndUldLoader,
+                LoaderType::BeltAndUldLoader,
+                LoaderType::UldLoader
+            ]
+        );
+        for value in ["0", "3", "7", "x"] {
+            assert_eq!(LoaderType::from_value(value).to_value(), value);
+        }
+    }
+
+    #[test]
+    fn walker_types_and_parking_systems_round_trip() {
+        assert_eq!(WalkerType::from_value("GsxJetwayPassenger"), WalkerType::JetwayPassenger);
+        for value in ["GsxJetwayPassenger", "GsxBus"] {
+            assert_eq!(WalkerType::from_value(value).to_value(), value);
+        }
+
+        assert_eq!(
+            ParkingSystem::from_value("SafeDockTS24Grey"),
+            ParkingSystem::SafeDock(String::from("TS24Grey"))
+        );
+        for value in ["", "Marshaller", "SafeDockTS24Grey", "SafeDock", "Apis"] {
+            assert_eq!(ParkingSystem::from_value(value).to_value(), value);
+        }
+    }
+
+    #[test]
+    fn flags_round_trip() {
+        assert_eq!(Flag::from_value("1"), Flag::Yes);
+        for value in ["0", "1", "2", "yes"] {
+            assert_eq!(Flag::from_value(value).to_value(), value);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::{collections::HashMap, fmt};
+
+use geoutils::Location;
+use uuid::Uuid;
+use walkers::Position;
+
+use super::GsxSection;
+
+/// A conflict of a stand with another stand, found by [`find_conflicts`]. Every conflict is kept
+/// by both stands, each from its own side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub other_section_id: Uuid,
+    pub other_name: String,
+    pub kind: ConflictKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictKind {
+    /// The wingspan circles of both stands overlap by this many meters.
+    WingspanOverlap(f64),
+    /// The pushback with this label ends within the wingspan circle of the other stand.
+    PushbackEndsOnStand(String),
+    /// The pushback of the other stand with this label ends within this stand's wingspan circle.
+    StandBlockedByPushback(String),
+    /// A pushback of this stand crosses one of the other stand.
+    PushbackPathsCross {
+        pushback: String,
+        other_pushback: String,
+    },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ConflictKind::WingspanOverlap(meters) => write!(
+                f,
+                "wingspan overlaps with {} by {:.1} m",
+                self.other_name, meters
+            ),
+            ConflictKind::PushbackEndsOnStand(pushback) => {
+                write!(f, "pushback '{}' ends on {}", pushback, self.other_name)
+            }
+            ConflictKind::StandBlockedByPushback(other_pushback) => write!(
+                f,
+                "pushback '{}' of {} ends on this stand",
+                other_pushback, self.other_name
+            ),
+            ConflictKind::PushbackPathsCross {
+                pushback,
+                other_pushback,
+            } => write!(
+                f,
+                "pushback '{}' crosses pushback '{}' of {}",
+                pushback, other_pushback, self.other_name
+            ),
+        }
+    }
+}
+
+/// Where the aircraft stands: the parking position, or the position the stand is shown at.
+pub fn stand_center(section: &GsxSection) -> Option<Position> {
+    section
+        .parking_position
+        .or(section.position)
+        .map(|gsx_position| gsx_position.position)
+}
+
+/// Radius in meters of the circle the wings of the largest aircraft of the stand sweep: the larger
+/// of `radiusleft` and `radiusright`, or half of `maxwingspan` if the profile has neither.
+pub fn wingspan_radius(section: &GsxSection) -> Option<f64> {
+    match (section.radius_left, section.radius_right) {
+        (Some(left), Some(right)) => Some(left.max(right)),
+        (Some(radius), None) | (None, Some(radius)) => Some(radius),
+        (None, None) => section.max_wingspan.map(|max_wingspan| max_wingspan / 2.0),
+    }
+}
+
+/// Checks every pair of stands for overlapping wingspan circles, pushbacks that end on the other
+/// stand and pushbacks that cross each other. Returns the conflicts of every stand that has some.
+pub fn find_conflicts(sections: &[GsxSection]) -> HashMap<Uuid, Vec<Conflict>> {
+    let mut conflicts: HashMap<Uuid, Vec<Conflict>> = HashMap::new();
+    let mut add_conflict = |section: &GsxSection, other: &GsxSection, kind: ConflictKind| {
+        conflicts.entry(section.id).or_default().push(Conflict {
+            other_section_id: other.id,
+            other_name: other.name.clone(),
+            kind,
+        });
+    };
+
+    for (index, section) in sections.iter().enumerate() {
+        for other in sections.iter().skip(index + 1) {
+            let circle = stand_center(section).zip(wingspan_radius(section));
+            let other_circle = stand_center(other).zip(wingspan_radius(other));
+
+            if let (Some((center, radius)), Some((other_center, other_radius))) =
+                (circle, other_circle)
+            {
+                let overlap = radius + other_radius - distance(center, other_center);
+                if overlap > 0.0 {
+                    add_conflict(section, other, ConflictKind::WingspanOverlap(overlap));
+                    add_conflict(other, section, ConflictKind::WingspanOverlap(overlap));
+                }
+            }
+
+            for (pushing, standing, standing_circle) in
+                [(section, other, other_circle), (other, section, circle)]
+            {
+                let Some((center, radius)) = standing_circle else {
+                    continue;
+                };
+                for pushback_path in pushing.pushback_paths.iter() {
+                    if distance(pushback_path.end.position, center) < radius {
+                        let label = pushback_path.label();
+                        add_conflict(
+                            pushing,
+                            standing,
+                            ConflictKind::PushbackEndsOnStand(label.clone()),
+                        );
+                        add_conflict(
+                            standing,
+                            pushing,
+                            ConflictKind::StandBlockedByPushback(label),
+                        );
+                    }
+                }
+            }
+
+            for pushback_path in section.pushback_paths.iter() {
+                let waypoints: Vec<Position> = pushback_path
+                    .waypoints()
+                    .iter()
+                    .map(|waypoint| waypoint.position)
+                    .collect();
+                for other_pushback_path in other.pushback_paths.iter() {
+                    let other_waypoints: Vec<Position> = other_pushback_path
+                        .waypoints()
+                        .iter()
+                        .map(|waypoint| waypoint.position)
+                        .collect();
+                    if paths_cross(&waypoints, &other_waypoints) {
+                        let pushback = pushback_path.label();
+                        let other_pushback = other_pushback_path.label();
+                        add_conflict(
+                            section,
+                            other,
+                            ConflictKind::PushbackPathsCross {
+                                pushback: pushback.clone(),
+                                other_pushback: other_pushback.clone(),
+                            },
+                        );
+                        add_conflict(
+                            other,
+                            section,
+                            ConflictKind::PushbackPathsCross {
+                                pushback: other_pushback,
+                                other_pushback: pushback,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+fn distance(a: Position, b: Position) -> f64 {
+    Location::new(a.lat(), a.lon())
+        .haversine_distance_to(&Location::new(b.lat(), b.lon()))
+        .meters()
+}
+
+/// Whether any leg of one path crosses a leg of the other. Legs that only touch don't count, so
+/// pushbacks that share a waypoint are fine.
+fn paths_cross(waypoints: &[Position], other_waypoints: &[Position]) -> bool {
+    let Some(&origin) = waypoints.first() else {
+        return false;
+    };
+    let points: Vec<(f64, f64)> = waypoints
+        .iter()
+        .map(|&position| to_meters(origin, position))
+        .collect();
+    let other_points: Vec<(f64, f64)> = other_waypoints
+        .iter()
+        .map(|&position| to_meters(origin, position))
+        .collect();
+    points.windows(2).any(|leg| {
+        other_points
+            .windows(2)
+            .any(|other_leg| legs_cross(leg[0], leg[1], other_leg[0], other_leg[1]))
+    })
+}
+
+/// Meters east and north of `origin`. Precise enough for the size of an airport.
+fn to_meters(origin: Position, position: Position) -> (f64, f64) {
+    const METRES_PER_DEGREE: f64 = 111_320.0;
+    (
+        (position.lon() - origin.lon()) * METRES_PER_DEGREE * origin.lat().to_radians().cos(),
+        (position.lat() - origin.lat()) * METRES_PER_DEGREE,
+    )
+}
+
+fn legs_cross(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> bool {
+    // Which side of the line through the first two points the third one is on
+    let side = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    };
+    side(a, b, c) * side(a, b, d) < 0.0 && side(c, d, a) * side(c, d, b) < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gsx_ini_parser::PushbackKind;
+
+    use crate::core::{GsxPosition, GsxPushbackPath};
+
+    /// Meters east and north of a point at the airport.
+    fn position(east: f64, north: f64) -> Position {
+        const METRES_PER_DEGREE: f64 = 111_320.0;
+        let latitude: f64 = 47.45;
+        Position::from_lat_lon(
+            latitude + north / METRES_PER_DEGREE,
+            8.55 + east / (METRES_PER_DEGREE * latitude.to_radians().cos()),
+        )
+    }
+
+    fn pushback(label: &str, waypoints: &[(f64, f64)]) -> GsxPushbackPath {
+        let mut waypoints = waypoints.iter().map(|&(east, north)| GsxPosition {
+            position: position(east, north),
+            heading: None,
+        });
+        let start = waypoints.next();
+        let mut approach: Vec<GsxPosition> = waypoints.collect();
+        let end = approach.pop().unwrap();
+        GsxPushbackPath {
+            kind: PushbackKind::Custom,
+            label: Some(String::from(label)),
+            start,
+            approach,
+            end,
+        }
+    }
+
+    fn stand(name: &str, center: Position, radius: Option<f64>) -> GsxSection {
+        GsxSection {
+            id: Uuid::new_v4(),
+            name: String::from(name),
+            stand_id: None,
+            position: None,
+            pushback_paths: vec![],
+            parking_position: Some(GsxPosition {
+                position: center,
+                heading: None,
+            }),
+            jetway_position: None,
+            stairs_front_position: None,
+            stairs_middle_position: None,
+            stairs_rear_position: None,
+            baggage_loader_front_position: None,
+            baggage_loader_main_position: None,
+            baggage_loader_rear_position: None,
+            baggage_train_front_position: None,
+            baggage_train_main_position: None,
+            baggage_train_rear_position: None,
+            parking_system_stop_position: None,
+            parking_system_object_position: None,
+            parking_type: None,
+            pushback_directions: None,
+            loader_types: vec![],
+            walker_type: None,
+            parking_system: None,
+            max_wingspan: None,
+            radius_left: radius,
+            radius_right: None,
+            gate_distance_threshold: None,
+            airline_codes: vec![],
+            has_jetway: None,
+            dont_create_jetways: None,
+            no_passenger_stairs: None,
+            no_passenger_bus: None,
+            disable_pax_barriers: None,
+            underground_refueling: None,
+            ignore_icao_prefixes: None,
+            ignore_preferred_exit: None,
+            snap_left_pushback: None,
+            snap_right_pushback: None,
+            user_customized: None,
+            conflicts: vec![],
+            invalid_values: vec![],
+        }
+    }
+
+    fn kinds(conflicts: &HashMap<Uuid, Vec<Conflict>>, section: &GsxSection) -> Vec<ConflictKind> {
+        conflicts
+            .get(&section.id)
+            .map(|conflicts| conflicts.iter().map(|conflict| conflict.kind.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn finds_overlapping_wingspans() {
+        let (center, other_center) = (position(0.0, 0.0), position(40.0, 0.0));
+        let radius = distance(center, other_center) / 2.0 + 1.0;
+        let sections = [
+            stand("A 1", center, Some(radius)),
+            stand("A 2", other_center, Some(radius)),
+        ];
+        let conflicts = find_conflicts(&sections);
+
+        for (section, other) in [(&sections[0], &sections[1]), (&sections[1], &sections[0])] {
+            let section_conflicts = &conflicts[&section.id];
+            assert_eq!(section_conflicts.len(), 1);
+            assert_eq!(section_conflicts[0].other_section_id, other.id);
+            match section_conflicts[0].kind {
+                ConflictKind::WingspanOverlap(meters) => assert!((meters - 2.0).abs() < 1e-6),
+                ref kind => panic!("unexpected conflict {:?}", kind),
+            }
+        }
+    }
+
+    #[test]
+    fn touching_wingspans_dont_conflict() {
+        let (center, other_center) = (position(0.0, 0.0), position(40.0, 0.0));
+        let radius = distance(center, other_center) / 2.0;
+        let sections = [
+            stand("A 1", center, Some(radius)),
+            stand("A 2", other_center, Some(radius)),
+        ];
+        assert!(find_conflicts(&sections).is_empty());
+    }
+
+    #[test]
+    fn finds_pushbacks_ending_on_another_stand() {
+        let mut pushing = stand("A 1", position(0.0, 0.0), Some(20.0));
+        pushing.pushback_paths = vec![
+            pushback("Tail north", &[(0.0, 0.0), (0.0, 90.0)]),
+            pushback("Tail south", &[(0.0, 0.0), (0.0, -90.0)]),
+        ];
+        let standing = stand("B 1", position(0.0, 100.0), Some(20.0));
+        let sections = [pushing, standing];
+        let conflicts = find_conflicts(&sections);
+
+        assert_eq!(
+            kinds(&conflicts, &sections[0]),
+            vec![ConflictKind::PushbackEndsOnStand(String::from("Tail north"))]
+        );
+        assert_eq!(
+            kinds(&conflicts, &sections[1]),
+            vec![ConflictKind::StandBlockedByPushback(String::from("Tail north"))]
+        );
+    }
+
+    #[test]
+    fn finds_crossing_pushbacks() {
+        let mut section = stand("A 1", position(0.0, 0.0), None);
+        section.pushback_paths = vec![pushback("North", &[(0.0, 0.0), (0.0, 50.0), (0.0, 100.0)])];
+        let mut other = stand("A 2", position(-50.0, 75.0), None);
+        other.pushback_paths = vec![pushback("East", &[(-50.0, 75.0), (50.0, 75.0)])];
+        let sections = [section, other];
+        let conflicts = find_conflicts(&sections);
+
+        assert_eq!(
+            kinds(&conflicts, &sections[0]),
+            vec![ConflictKind::PushbackPathsCross {
+                pushback: String::from("North"),
+                other_pushback: String::from("East"),
+            }]
+        );
+        assert_eq!(
+            kinds(&conflicts, &sections[1]),
+            vec![ConflictKind::PushbackPathsCross {
+                pushback: String::from("East"),
+                other_pushback: String::from("North"),
+            }]
+        );
+    }
+
+    #[test]
+    fn touching_pushbacks_dont_cross() {
+        let path = [position(0.0, 0.0), position(0.0, 100.0)];
+        // Leaving from the end of the path
+        assert!(!paths_cross(&path, &[position(0.0, 100.0), position(50.0, 100.0)]));
+        // Ending on the middle of the path
+        assert!(!paths_cross(&path, &[position(-50.0, 50.0), position(0.0, 50.0)]));
+        // Going on in the same direction
+        assert!(!paths_cross(&path, &[position(0.0, 100.0), position(0.0, 150.0)]));
+        // Passing by
+        assert!(!paths_cross(&path, &[position(10.0, 0.0), position(10.0, 100.0)]));
+        assert!(paths_cross(&path, &[position(-50.0, 50.0), position(50.0, 50.0)]));
+        assert!(!paths_cross(&[], &path));
+    }
+}
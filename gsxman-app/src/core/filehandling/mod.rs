@@ -1,25 +1,35 @@
 use std::{
     collections::HashMap,
-    fs::{self, File},
-    io::{self, Read, Write},
+    fs,
+    io,
     path::{Path, PathBuf},
     time::SystemTime,
 };
 
 use geoutils::Location;
-use json::JsonValue;
 use regex::Regex;
-use tracing::{debug, error, warn};
+use serde_json::Value;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 use walkers::Position;
 
-use gsx_ini_parser;
+use gsx_ini_parser::{self, General, GeoPosition, IniDocument};
 
 use crate::util;
 
-use super::{Airport, GsxProfile, GsxSection, ProfileFile};
+use super::{
+    conflicts,
+    settings::{ProfileIdentity, ProfileRoot, ProfileRootSource, Settings, UserData, SCHEMA_VERSION},
+    validation,
+    attributes::{Flag, LoaderType, ParkingSystem, ParkingType, PushbackDirections, WalkerType},
+    Airport, GsxPosition, GsxProfile, GsxPushbackPath, GsxSection, PendingMerge, ProfileFile,
+};
 
 mod profileimporter;
+mod userdatastore;
+
+const ORIGINALS_FOLDER: &str = "gsxman_originals";
+const USER_DATA_FILE: &str = "gsxman_userdata.json";
 
 // Required Data Loading
 
@@ -46,50 +56,96 @@ pub fn get_airport_data() -> HashMap<String, Airport> {
     return_map
 }
 
-pub fn get_user_data() -> JsonValue {
-    let mut file = match File::options()
-        .read(true)
-        .write(true)
-        .create(true)
-        .open("gsxman_userdata.json")
-    {
-        Ok(f) => f,
-        Err(_) => panic!("Could not create Userdata File!"),
+pub fn get_user_data() -> UserData {
+    let user_data_path = get_user_data_path();
+    // Before the config directory the user data was kept in the working directory
+    let legacy_path = Path::new(USER_DATA_FILE);
+    let path = if !user_data_path.exists() && legacy_path.exists() {
+        info!("Reading user data from {:?}", legacy_path);
+        legacy_path
+    } else {
+        user_data_path.as_path()
     };
-    let mut data = String::new();
-    file.read_to_string(&mut data).unwrap();
 
-    if data.is_empty() {
-        data += "{}";
-    }
-    json::parse(data.as_str()).unwrap()
+    read_user_data(path)
 }
 
-pub fn write_user_data(user_data: &JsonValue) {
-    let mut file = match File::options()
-        .read(true)
-        .write(true)
-        .create(true)
-        .open("gsxman_userdata.json")
-    {
-        Ok(f) => f,
-        Err(_) => panic!("Could not create Userdata File!"),
-    };
+/// Reads the user data at `path`. A file of a newer version that can't be read is left alone, the
+/// user data is then empty and [read only](UserData::read_only), so it isn't replaced.
+fn read_user_data(path: &Path) -> UserData {
+    let value = userdatastore::load(path);
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    match UserData::from_value(value) {
+        Ok(user_data) => user_data,
+        Err(error) if version > SCHEMA_VERSION => {
+            error!("Could not read user data {:?} of version {}: {}", path, version, error);
+            UserData {
+                read_only: true,
+                ..UserData::default()
+            }
+        }
+        Err(error) => {
+            // Keep the file for the user, but neither read it again nor take it as backup
+            error!("Could not read user data {:?}: {}", path, error);
+            userdatastore::set_aside(path);
+            UserData::default()
+        }
+    }
+}
 
-    if let Err(_) = file.write(&*user_data.dump().as_bytes()) {
-        panic!("Could not write to Userdata File!");
+pub fn write_user_data(user_data: &UserData) {
+    let user_data_path = get_user_data_path();
+    if user_data.read_only {
+        warn!("Not writing {:?}, it is from a newer version of GSXManager", user_data_path);
+        return;
     }
+    let write_result = fs::create_dir_all(util::get_config_path())
+        .and_then(|_| serde_json::to_value(user_data).map_err(io::Error::from))
+        .and_then(|value| userdatastore::store(&user_data_path, &value));
+    if let Err(error) = write_result {
+        error!("Could not write {:?}: {}", user_data_path, error);
+    }
+}
+
+fn get_user_data_path() -> PathBuf {
+    util::get_config_path().join(USER_DATA_FILE)
 }
 
 // GSX Profile handling
 
+/// The folders to read profiles from: the detected ones and the ones the user added, or only the
+/// ones given on the command line or in the environment.
+pub fn get_gsx_profile_roots(settings: &Settings) -> Vec<ProfileRoot> {
+    if let Some(roots) = util::get_gsx_profile_roots_override() {
+        info!("Reading profiles only from {:?}", roots);
+        return roots;
+    }
+    let mut roots = util::get_default_gsx_profile_roots();
+    for root in settings.profile_roots.iter() {
+        if !roots.iter().any(|other| other.path == root.path) {
+            roots.push(ProfileRoot::new(&root.label, root.path.clone(), ProfileRootSource::User));
+        }
+    }
+    roots
+}
+
 pub fn get_installed_gsx_profiles(
     airport_data: &HashMap<String, Airport>,
+    profile_roots: &[ProfileRoot],
 ) -> HashMap<Uuid, ProfileFile> {
     let mut installed_config_files: HashMap<Uuid, ProfileFile> = HashMap::new();
-    let gsx_path = util::get_gsx_profile_path();
+    for profile_root in profile_roots {
+        read_profile_root(airport_data, profile_root, &mut installed_config_files);
+    }
+    installed_config_files
+}
 
-    if let Ok(gsx_dir) = fs::read_dir(gsx_path) {
+fn read_profile_root(
+    airport_data: &HashMap<String, Airport>,
+    profile_root: &ProfileRoot,
+    installed_config_files: &mut HashMap<Uuid, ProfileFile>,
+) {
+    if let Ok(gsx_dir) = fs::read_dir(&profile_root.path) {
         let entries = gsx_dir
             .map(|res| res.map(|e| e.path()))
             .collect::<Result<Vec<_>, io::Error>>()
@@ -126,18 +182,34 @@ pub fn get_installed_gsx_profiles(
                 };
 
                 let mut creator = String::from("");
-                let parse_result =
-                    gsx_ini_parser::parse_file(path_entry.as_os_str().to_str().unwrap());
+                let mut identity = ProfileIdentity::new(icao_code, "", None);
+                let mut parse_diagnostics = vec![];
+                let mut problems = vec![];
+                let parse_result = gsx_ini_parser::parse_document(path_entry);
                 if let Err(error) = parse_result {
                     error!("{}", error);
                 } else {
-                    let data_map = parse_result.unwrap();
+                    let parsed_file = parse_result.unwrap();
 
-                    if let Some(general_section) = data_map.get("general") {
-                        if let Some(creator_string) = general_section.get("creator") {
-                            creator = creator_string.to_owned();
-                        }
+                    let document = &parsed_file.document;
+                    match gsx_ini_parser::from_section::<General>(document, "general") {
+                        Ok(general) => creator = general.creator.unwrap_or_default(),
+                        Err(error) => warn!("{}: {}", &file_name, error),
+                    }
+                    identity = ProfileIdentity::new(icao_code, &creator, Some(document));
+
+                    for diagnostic in parsed_file.diagnostics.iter() {
+                        debug!("{}: {}", &file_name, diagnostic);
+                    }
+                    problems = validation::validate_document(
+                        document,
+                        &parsed_file.diagnostics,
+                        airport,
+                    );
+                    for problem in problems.iter() {
+                        debug!("{}: {}", &file_name, problem);
                     }
+                    parse_diagnostics = parsed_file.diagnostics;
                 }
 
                 let mut config = ProfileFile::new(
@@ -149,9 +221,16 @@ pub fn get_installed_gsx_profiles(
                     creator,
                     String::from(""), //TODO
                 );
+                config.root = profile_root.clone();
+                config.identity = identity;
+                config.parse_diagnostics = parse_diagnostics;
+                config.problems = problems;
 
+                // Every simulator has its own folder, so a profile in another one is no duplicate
                 for (_, profile_file) in installed_config_files.iter_mut() {
-                    if profile_file.airport.icao == config.airport.icao {
+                    if profile_file.airport.icao == config.airport.icao
+                        && profile_file.root.path == config.root.path
+                    {
                         warn!("Duplicate Profile for Airport {}", config.airport.icao);
                         profile_file.has_duplicate_error = true;
                         config.has_duplicate_error = true;
@@ -162,98 +241,200 @@ pub fn get_installed_gsx_profiles(
             };
         }
     }
-
-    installed_config_files
 }
 
 pub fn load_profile_data(file: &mut ProfileFile) {
-    let parse_result = gsx_ini_parser::parse_file(file.file_location.as_os_str().to_str().unwrap());
+    let parse_result = gsx_ini_parser::parse_document(&file.file_location);
     if let Err(error) = parse_result {
         error!("{}", error);
         return;
     }
-    let data_map = parse_result.unwrap();
+    let document = parse_result.unwrap().document;
     let mut profile_data = GsxProfile::new();
 
-    if let Some(_general_section) = data_map.get("general") {
-        //TODO: Handle Deice areas
+    match gsx_ini_parser::from_section::<General>(&document, "general") {
+        Ok(general) => profile_data.deice_areas = general.deice_areas,
+        Err(error) => warn!("{}", error),
     }
 
-    for (section_name, values) in data_map.iter() {
-        if !values.contains_key("pushback_pos") || section_name.eq_ignore_ascii_case("general") {
-            // For now we only handle sections that have a pushback_pos
-            continue;
-        }
-
-        let name = section_name.clone();
-
-        let position = position_string_to_position(&values["pushback_pos"]);
-        if position.is_none() {
-            warn!("Section {} has no Position", &name);
+    for section_name in document.section_names() {
+        if section_name.eq_ignore_ascii_case("general") {
             continue;
         }
-        let position = position.unwrap();
-
-        let mut pushback_label_left = None;
-        let mut pushback_position_left = None;
-        let mut pushback_label_right = None;
-        let mut pushback_position_right = None;
-        if let Some(pushback_labels) = values.get("pushbacklabels") {
-            let pushback_labels: Vec<&str> = pushback_labels.split('|').collect();
-            if !pushback_labels.is_empty() {
-                if let Some(string_value) = values.get("pushbackleftpos") {
-                    pushback_label_left = Some(pushback_labels[0].to_string());
-                    pushback_position_left = position_string_to_position(string_value);
-                }
 
-                if pushback_labels.len() > 1 {
-                    if let Some(string_value) = values.get("pushbackrightpos") {
-                        pushback_label_right = Some(pushback_labels[1].to_string());
-                        pushback_position_right = position_string_to_position(string_value);
-                    }
-                }
+        let (stand, invalid_values) = match validation::read_stand(&document, section_name) {
+            Ok(stand) => stand,
+            Err(error) => {
+                warn!("{}", error);
+                continue;
             }
+        };
+        for error in invalid_values.iter() {
+            warn!("{}", error);
         }
 
-        let section = GsxSection {
+        let map_position = |position: &Option<GeoPosition>| position.as_ref().map(to_gsx_position);
+        let flag = |key: &str| stand.extra.get(key).map(|value| Flag::from_value(value));
+        let pushback_paths = stand
+            .pushback_paths()
+            .into_iter()
+            .map(|pushback_path| GsxPushbackPath {
+                kind: pushback_path.kind,
+                label: pushback_path.label,
+                start: pushback_path.start.as_ref().map(to_gsx_position),
+                approach: pushback_path.approach.iter().map(to_gsx_position).collect(),
+                end: to_gsx_position(&pushback_path.end),
+            })
+            .collect();
+        let mut section = GsxSection {
             id: Uuid::new_v4(),
-            name,
-            position,
-            pushback_label_left,
-            pushback_position_left,
-            pushback_label_right,
-            pushback_position_right,
+            stand_id: stand.id(),
+            name: stand.name,
+            position: None,
+            pushback_paths,
+            parking_position: map_position(&stand.this_parking_pos),
+            jetway_position: map_position(&stand.jetway_pos),
+            stairs_front_position: map_position(&stand.stairs_front_pos),
+            stairs_middle_position: map_position(&stand.stairs_middle_pos),
+            stairs_rear_position: map_position(&stand.stairs_rear_pos),
+            baggage_loader_front_position: map_position(&stand.baggage_loader_front_pos),
+            baggage_loader_main_position: map_position(&stand.baggage_loader_main_pos),
+            baggage_loader_rear_position: map_position(&stand.baggage_loader_rear_pos),
+            baggage_train_front_position: map_position(&stand.baggage_train_front_pos),
+            baggage_train_main_position: map_position(&stand.baggage_train_main_pos),
+            baggage_train_rear_position: map_position(&stand.baggage_train_rear_pos),
+            parking_system_stop_position: map_position(&stand.parkingsystem_stopposition),
+            parking_system_object_position: stand.parkingsystem_objectposition.map(|position| {
+                GsxPosition {
+                    position: Position::from_lat_lon(position.latitude, position.longitude),
+                    heading: Some(position.heading),
+                }
+            }),
+            parking_type: stand
+                .parking_type
+                .map(|parking_type| ParkingType::from_value(&parking_type)),
+            pushback_directions: stand
+                .pushback
+                .map(|pushback| PushbackDirections::from_value(&pushback)),
+            loader_types: stand
+                .extra
+                .get("loadertype")
+                .map(|value| LoaderType::from_list(value))
+                .unwrap_or_default(),
+            walker_type: stand.extra.get("walkertype").map(|value| WalkerType::from_value(value)),
+            parking_system: stand
+                .extra
+                .get("parkingsystem")
+                .map(|value| ParkingSystem::from_value(value)),
+            max_wingspan: stand.max_wingspan,
+            radius_left: stand.radius_left,
+            radius_right: stand.radius_right,
+            gate_distance_threshold: stand.gate_distance_threshold,
+            airline_codes: stand.airline_codes,
+            has_jetway: flag("hasjetway"),
+            dont_create_jetways: flag("dontcreatejetways"),
+            no_passenger_stairs: flag("nopassengerstairs"),
+            no_passenger_bus: flag("nopassengerbus"),
+            disable_pax_barriers: flag("disablepaxbarriers"),
+            underground_refueling: flag("undergroundrefueling"),
+            ignore_icao_prefixes: flag("ignoreicaoprefixes"),
+            ignore_preferred_exit: flag("ignorepreferredexit"),
+            snap_left_pushback: flag("snapleftpushbackpos"),
+            snap_right_pushback: flag("snaprightpushbackpos"),
+            user_customized: flag("usercustomized"),
+            conflicts: vec![],
+            invalid_values,
         };
+        // Stands are shown at their pushback position if they have one, like before
+        section.position = map_position(&stand.pushback_pos).or_else(|| {
+            section
+                .service_positions()
+                .first()
+                .map(|(_, position)| *position)
+        });
 
         profile_data.sections.push(section);
     }
 
+    let mut conflicts = conflicts::find_conflicts(&profile_data.sections);
+    for section in profile_data.sections.iter_mut() {
+        section.conflicts = conflicts.remove(&section.id).unwrap_or_default();
+    }
+
     file.profile_data = Some(profile_data);
 }
 
 #[inline]
-fn position_string_to_position(string_value: &str) -> Option<Position> {
-    let mut pos = Position::from_lat_lon(0.0, 0.0);
-    let coord_strings: Vec<&str> = string_value.split(' ').collect();
-    let lat = coord_strings[0].parse::<f64>();
-    let lon = coord_strings[1].parse::<f64>();
-    if lat.is_ok() && lon.is_ok() {
-        pos = Position::from_lat_lon(lat.unwrap(), lon.unwrap());
+fn to_gsx_position(position: &GeoPosition) -> GsxPosition {
+    GsxPosition {
+        position: Position::from_lat_lon(position.latitude, position.longitude),
+        heading: position.heading,
     }
-    Some(pos)
 }
 
-pub fn import_profile_file_dialog() {
+/// Imports a profile chosen by the user into `profile_root`.
+pub fn import_profile_file_dialog(profile_root: &Path) -> Option<PendingMerge> {
     if let Some(path) = rfd::FileDialog::new()
         .add_filter("GSX-Profile", &["ini", "zip", "rar"])
         .set_directory("/")
         .set_title("Import new Profile")
         .pick_file()
     {
-        profileimporter::import_from_path(path);
+        return profileimporter::import_from_path(path, profile_root);
+    }
+    None
+}
+
+/// Writes the merged profile over the installed one and keeps the update as the original for the
+/// next merge.
+pub fn finish_merge(pending_merge: PendingMerge) {
+    let document = pending_merge
+        .result
+        .into_document(&pending_merge.resolutions);
+    // The installed profile must not be left half written
+    let write_result =
+        userdatastore::replace(&pending_merge.installed_file, |file| document.write_to(file));
+    match write_result {
+        Ok(_) => {
+            debug!("Merged update into {:?}", &pending_merge.installed_file);
+            store_original(&pending_merge.upstream_file, &pending_merge.installed_file);
+        }
+        Err(error) => error!("{}", error),
+    }
+}
+
+/// Keeps a copy of a profile as it was imported, the base of a later three-way merge.
+fn store_original(imported_file: &Path, installed_file: &Path) {
+    let copy_result = fs::create_dir_all(util::get_config_path().join(ORIGINALS_FOLDER))
+        .and_then(|_| fs::copy(imported_file, get_original_path(installed_file)));
+    if let Err(error) = copy_result {
+        error!("Could not store original of {:?}: {}", installed_file, error);
+    }
+}
+
+fn get_original(installed_file: &Path) -> Option<IniDocument> {
+    // Originals stored before the config directory are in the working directory
+    let original_path = [
+        get_original_path(installed_file),
+        Path::new(ORIGINALS_FOLDER).join(installed_file.file_name().unwrap()),
+    ]
+    .into_iter()
+    .find(|original_path| original_path.exists())?;
+    match gsx_ini_parser::parse_document(original_path) {
+        Ok(parsed) => Some(parsed.document),
+        Err(error) => {
+            error!("{}", error);
+            None
+        }
     }
 }
 
+fn get_original_path(installed_file: &Path) -> PathBuf {
+    util::get_config_path()
+        .join(ORIGINALS_FOLDER)
+        .join(installed_file.file_name().unwrap())
+}
+
 pub fn delete_profile_file(profile_path_to_delete: &PathBuf) -> bool {
     let filename = profile_path_to_delete
         .file_name()
@@ -349,3 +530,44 @@ fn get_associated_python_file(gsx_profile_path: &Path) -> Option<PathBuf> {
 
     python_file
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new, empty folder for a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gsxman-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn keeps_unreadable_user_data_of_a_newer_version() {
+        let dir = test_dir("newer-user-data");
+        let path = dir.join(USER_DATA_FILE);
+        let newer = r#"{ "version": 3, "profiles": { "LSZH": { "notes": "Kept" } } }"#;
+        fs::write(&path, newer).unwrap();
+
+        let user_data = read_user_data(&path);
+        assert!(user_data.read_only);
+        assert!(user_data.profiles.is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+        assert!(!path.with_extension("json.unreadable").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sets_unreadable_user_data_aside() {
+        let dir = test_dir("unreadable-user-data");
+        let path = dir.join(USER_DATA_FILE);
+        fs::write(&path, r#"{ "version": 2, "profiles": { "LSZH": { "notes": "Lost" } } }"#).unwrap();
+
+        let user_data = read_user_data(&path);
+        assert!(!user_data.read_only);
+        assert!(!path.exists());
+        assert!(path.with_extension("json.unreadable").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,16 +1,147 @@
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use gsx_ini_parser::{IniDocument, Resolution};
+use tracing::{debug, error, warn};
+
+use crate::core::{filehandling::get_associated_python_file, PendingMerge};
 
 mod directimporter;
 mod zipimporter;
 mod rarimporter;
 
-pub fn import_from_path(path: PathBuf) {
+const MERGE_BUTTON: &str = "Merge";
+const INSTALL_BUTTON: &str = "Install as is";
+
+pub fn import_from_path(path: PathBuf, profile_root: &Path) -> Option<PendingMerge> {
     let path_extension = path.extension().unwrap().to_str().unwrap();
     if path_extension == "zip" {
-        zipimporter::import(path);
+        zipimporter::import(path, profile_root)
     } else if path_extension == "rar" {
-        rarimporter::import(path);
+        rarimporter::import(path, profile_root)
     } else {
-        directimporter::import(path);
+        directimporter::import(path, profile_root)
+    }
+}
+
+/// Copies the profile and its python file into the profile folder. If a profile for the same
+/// airport is already installed there, the update can be merged into it instead, which keeps local
+/// changes.
+fn install_profile(path: &Path, profile_root: &Path) -> Option<PendingMerge> {
+    if let Some(installed_file) = find_installed_profile(path, profile_root) {
+        let message_dialog = rfd::MessageDialog::new()
+            .set_buttons(rfd::MessageButtons::YesNoCancelCustom(
+                MERGE_BUTTON.to_string(),
+                INSTALL_BUTTON.to_string(),
+                "Cancel".to_string(),
+            ))
+            .set_description(format!(
+                "A profile for this airport is already installed ({}). Merge the update into it to keep your own changes?",
+                installed_file.file_name().unwrap().to_str().unwrap()
+            ))
+            .set_title("Profile already installed")
+            .set_level(rfd::MessageLevel::Info)
+            .show();
+        match message_dialog {
+            rfd::MessageDialogResult::Yes => return merge_into_installed(path, installed_file),
+            rfd::MessageDialogResult::Custom(button) if button == MERGE_BUTTON => {
+                return merge_into_installed(path, installed_file)
+            }
+            rfd::MessageDialogResult::No => (),
+            rfd::MessageDialogResult::Custom(button) if button == INSTALL_BUTTON => (),
+            _ => return None,
+        }
+    }
+
+    let to_path = profile_root.join(path.file_name().unwrap());
+    match fs::copy(path, &to_path) {
+        Ok(_) => {
+            super::store_original(path, &to_path);
+            if let Some(python_file) = get_associated_python_file(path) {
+                let to_path = profile_root.join(python_file.file_name().unwrap());
+                if let Err(error) = fs::copy(&python_file, to_path) {
+                    error!("{:?}", error);
+                }
+            }
+        }
+        Err(error) => {
+            error!("{:?}", error)
+        }
     }
-}
\ No newline at end of file
+    None
+}
+
+/// Profile in `profile_root` for the airport of the profile at `path`, matched by the ICAO prefix of
+/// the file name.
+fn find_installed_profile(path: &Path, profile_root: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?.to_uppercase();
+    let (icao_code, _) = file_name.split_once('-')?;
+    let entries = fs::read_dir(profile_root).ok()?;
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|installed_file| {
+            installed_file.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+                let name = name.to_uppercase();
+                name.ends_with(".INI") && name.split_once('-').is_some_and(|(icao, _)| icao == icao_code)
+            })
+        })
+        .min()
+}
+
+/// Three-way merge of the update at `path` into the installed profile, with the version it was
+/// installed from as the base. Merges without conflicts are written right away.
+fn merge_into_installed(path: &Path, installed_file: PathBuf) -> Option<PendingMerge> {
+    let upstream = match gsx_ini_parser::parse_document(path) {
+        Ok(parsed) => parsed.document,
+        Err(error) => {
+            error!("{}", error);
+            return None;
+        }
+    };
+    let local = match gsx_ini_parser::parse_document(&installed_file) {
+        Ok(parsed) => parsed.document,
+        Err(error) => {
+            error!("{}", error);
+            return None;
+        }
+    };
+    let base = super::get_original(&installed_file).unwrap_or_else(|| {
+        // Without the original every key both sides have but disagree on is a conflict
+        warn!("No original stored for {:?}, merging without one", &installed_file);
+        IniDocument::new()
+    });
+
+    let result = gsx_ini_parser::merge_documents(&base, &local, &upstream);
+    debug!("Merge into {:?} has {} conflict(s)", &installed_file, result.conflicts.len());
+    let pending_merge = PendingMerge {
+        installed_file,
+        upstream_file: path.to_path_buf(),
+        resolutions: vec![Resolution::Local; result.conflicts.len()],
+        result,
+    };
+    if pending_merge.result.conflicts.is_empty() {
+        super::finish_merge(pending_merge);
+        None
+    } else {
+        Some(pending_merge)
+    }
+}
+
+/// Archives often ship other ini files next to the profile (scenery or addon configs). Only files
+/// with GSX specific keys are offered for import.
+fn is_gsx_profile(document: &IniDocument) -> bool {
+    const GSX_KEYS: [&str; 5] = [
+        "pushback_pos",
+        "this_parking_pos",
+        "pushbacklabels",
+        "deice_areas",
+        "afcad_path",
+    ];
+    document.sections().iter().any(|section| {
+        GSX_KEYS
+            .iter()
+            .any(|gsx_key| section.contains_key(gsx_key))
+    })
+}
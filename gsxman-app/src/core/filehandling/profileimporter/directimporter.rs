@@ -1,24 +1,7 @@
-use std::{fs, path::PathBuf};
-use tracing::error;
+use std::path::{Path, PathBuf};
 
-use crate::{core::filehandling::get_associated_python_file, util};
+use crate::core::PendingMerge;
 
-pub fn import(path: PathBuf) {
-    let to_path = util::get_gsx_profile_path().join(path.file_name().unwrap());
-    match fs::copy(&path, to_path) {
-        Ok(_) => {
-            if let Some(python_file) = get_associated_python_file(&path) {
-                let to_path = util::get_gsx_profile_path().join(python_file.file_name().unwrap());
-                match fs::copy(&path, to_path) {
-                    Ok(_) => {}
-                    Err(error) => {
-                        error!("{:?}", error);
-                    }
-                }
-            }
-        }
-        Err(error) => {
-            error!("{:?}", error)
-        }
-    }
-}
\ No newline at end of file
+pub fn import(path: PathBuf, profile_root: &Path) -> Option<PendingMerge> {
+    super::install_profile(&path, profile_root)
+}
@@ -1,11 +1,16 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
 
-use tracing::error;
+use tracing::{debug, error, warn};
 use unrar::Archive;
 
-use crate::{core::filehandling::get_associated_python_file, util};
+use crate::core::PendingMerge;
 
-pub fn import(path: PathBuf) {
+use super::is_gsx_profile;
+
+pub fn import(path: PathBuf, profile_root: &Path) -> Option<PendingMerge> {
     let rar_file_name = path
         .components()
         .next_back()
@@ -17,13 +22,15 @@ pub fn import(path: PathBuf) {
     let extraction_path = path.parent().unwrap().to_path_buf().join(rar_file_name);
 
     let ini_path_to_import = prepare_import(path.as_os_str().to_str().unwrap(), &extraction_path);
-    import_ini(ini_path_to_import);
+    import_ini(ini_path_to_import, profile_root)
 }
 
-fn prepare_import(rar_file_name: &str, extraction_path: &PathBuf) -> PathBuf {
+fn prepare_import(rar_file_name: &str, extraction_path: &Path) -> PathBuf {
     let mut ini_files: Vec<String> = vec![];
     let mut py_files: Vec<String> = vec![];
 
+    // Read profiles and python files into memory first, so only actual GSX profiles are written
+    let mut files_to_extract: Vec<(PathBuf, Vec<u8>)> = vec![];
     match Archive::new(rar_file_name).open_for_processing() {
         Err(_) => {
             error!("Error opening RAR-Archive {}", rar_file_name);
@@ -31,25 +38,77 @@ fn prepare_import(rar_file_name: &str, extraction_path: &PathBuf) -> PathBuf {
         },
         Ok(mut archive) => {
             while let Ok(Some(header)) = archive.read_header() {
-                archive = if header.entry().is_file() {
-                    let extract_to = extraction_path
-                        .clone()
-                        .join(header.entry().filename.clone());
-                    let arch = header.extract_to(&extract_to).unwrap();
-                    let path_extension = extract_to.extension().unwrap().to_str().unwrap();
+                let Some(file_name) = enclosed_name(&header.entry().filename) else {
+                    warn!("{:?} would be extracted outside of the archive folder, skipping", header.entry().filename);
+                    archive = match header.skip() {
+                        Ok(archive) => archive,
+                        Err(error) => {
+                            error!("Error reading RAR-Archive {}: {}", rar_file_name, error);
+                            return PathBuf::new();
+                        }
+                    };
+                    continue;
+                };
+                let path_extension = file_name
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .unwrap_or_default()
+                    .to_owned();
+                archive = if header.entry().is_file() && (path_extension == "ini" || path_extension == "py") {
+                    let (data, arch) = match header.read() {
+                        Ok(read) => read,
+                        Err(error) => {
+                            error!("Error reading {:?} from RAR-Archive: {}", &file_name, error);
+                            return PathBuf::new();
+                        }
+                    };
                     if path_extension == "ini" {
-                        ini_files.push(String::from(extract_to.as_os_str().to_str().unwrap()));
-                    } else if path_extension == "py" {
-                        py_files.push(String::from(extract_to.as_os_str().to_str().unwrap()));
+                        match gsx_ini_parser::parse_document_reader(data.as_slice()) {
+                            Ok(parsed) if is_gsx_profile(&parsed.document) => {
+                                for diagnostic in parsed.diagnostics.iter() {
+                                    debug!("{:?}: {}", &file_name, diagnostic);
+                                }
+                                files_to_extract.push((file_name, data));
+                            }
+                            Ok(_) => warn!("{:?} is not a GSX Profile, skipping", &file_name),
+                            Err(error) => error!("Error reading {:?} from RAR-Archive: {}", &file_name, error),
+                        }
+                    } else {
+                        files_to_extract.push((file_name, data));
                     }
                     arch
                 } else {
-                    header.skip().unwrap()
+                    match header.skip() {
+                        Ok(archive) => archive,
+                        Err(error) => {
+                            error!("Error reading RAR-Archive {}: {}", rar_file_name, error);
+                            return PathBuf::new();
+                        }
+                    }
                 };
             }
         }
     };
 
+    for (file_name, data) in files_to_extract {
+        let extract_to = extraction_path.join(&file_name);
+        if let Some(parent) = extract_to.parent() {
+            if let Err(error) = fs::create_dir_all(parent) {
+                error!("{:?}", error);
+                continue;
+            }
+        }
+        if let Err(error) = fs::write(&extract_to, data) {
+            error!("{:?}", error);
+            continue;
+        }
+        if file_name.extension().is_some_and(|extension| extension == "ini") {
+            ini_files.push(String::from(extract_to.as_os_str().to_str().unwrap()));
+        } else {
+            py_files.push(String::from(extract_to.as_os_str().to_str().unwrap()));
+        }
+    }
+
     let mut ini_path_to_import = PathBuf::new();
     match ini_files.len() {
         0 => error!("No ini-Files found in Zip Archive"),
@@ -59,7 +118,7 @@ fn prepare_import(rar_file_name: &str, extraction_path: &PathBuf) -> PathBuf {
         _ => {
             if let Some(path) = rfd::FileDialog::new()
                 .add_filter("GSX-Profile", &["ini"])
-                .set_directory(&extraction_path)
+                .set_directory(extraction_path)
                 .set_title("Multiple Ini-Files found. Choose which one to import")
                 .pick_file()
             {
@@ -77,7 +136,7 @@ fn prepare_import(rar_file_name: &str, extraction_path: &PathBuf) -> PathBuf {
         _ => {
             if let Some(path) = rfd::FileDialog::new()
                 .add_filter("GSX-Profile", &["py"])
-                .set_directory(&extraction_path)
+                .set_directory(extraction_path)
                 .set_title("Multiple possible Py-Files found. Choose which one to import or Cancel if none")
                 .pick_file()
             {
@@ -99,26 +158,37 @@ fn prepare_import(rar_file_name: &str, extraction_path: &PathBuf) -> PathBuf {
     ini_path_to_import
 }
 
-fn import_ini(path: PathBuf) {
+/// The name of an archive entry if it stays inside the folder it is extracted to, like
+/// `ZipFile::enclosed_name`. Absolute names and names with `..` are rejected.
+fn enclosed_name(file_name: &Path) -> Option<PathBuf> {
+    let is_enclosed = !file_name.as_os_str().is_empty()
+        && file_name
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    is_enclosed.then(|| file_name.to_owned())
+}
+
+fn import_ini(path: PathBuf, profile_root: &Path) -> Option<PendingMerge> {
     if path.to_str().unwrap().is_empty() {
-        return;
+        return None;
     }
 
-    let to_path = util::get_gsx_profile_path().join(path.file_name().unwrap());
-    match fs::copy(&path, to_path) {
-        Ok(_) => {
-            if let Some(python_file) = get_associated_python_file(&path) {
-                let to_path = util::get_gsx_profile_path().join(python_file.file_name().unwrap());
-                match fs::copy(&path, to_path) {
-                    Ok(_) => {}
-                    Err(error) => {
-                        error!("{:?}", error);
-                    }
-                }
-            }
-        }
-        Err(error) => {
-            error!("{:?}", error)
-        }
+    super::install_profile(&path, profile_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_entries_outside_of_the_archive_folder() {
+        assert_eq!(
+            enclosed_name(Path::new("LSZH/lszh-fsdt.ini")),
+            Some(PathBuf::from("LSZH/lszh-fsdt.ini"))
+        );
+        assert_eq!(enclosed_name(Path::new("../lszh-fsdt.ini")), None);
+        assert_eq!(enclosed_name(Path::new("LSZH/../../lszh-fsdt.ini")), None);
+        assert_eq!(enclosed_name(Path::new("/etc/lszh-fsdt.ini")), None);
+        assert_eq!(enclosed_name(Path::new("")), None);
     }
 }
@@ -1,15 +1,20 @@
 use std::{
     fs::{self, File},
-    io::{self, BufReader},
-    path::PathBuf,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
 };
 
-use tracing::{debug, error};
-use zip::{read::ZipFile, ZipArchive};
+use tracing::{debug, error, warn};
+use zip::{
+    result::{ZipError, ZipResult},
+    ZipArchive,
+};
+
+use crate::core::PendingMerge;
 
-use crate::{core::filehandling::get_associated_python_file, util};
+use super::is_gsx_profile;
 
-pub fn import(path: PathBuf) {
+pub fn import(path: PathBuf, profile_root: &Path) -> Option<PendingMerge> {
     let zip_file_name = path
         .components()
         .next_back()
@@ -31,61 +36,76 @@ pub fn import(path: PathBuf) {
                     &extraction_path,
                     &ini_path_to_import,
                 );
-                import_ini(ini_path_to_import);
+                import_ini(ini_path_to_import, profile_root)
             } else {
                 error!("Error opening Zip Archive");
+                None
             }
         }
-        Err(err) => error!("{:?}", err),
+        Err(err) => {
+            error!("{:?}", err);
+            None
+        }
     }
 }
 
-fn read_files_from_zip_archive(
-    reader: BufReader<File>,
-) -> Option<(ZipArchive<BufReader<File>>, Vec<String>, Vec<String>)> {
+/// The opened archive with the names of its profiles and python files.
+type ZipContents = (ZipArchive<BufReader<File>>, Vec<String>, Vec<String>);
+
+fn read_files_from_zip_archive(reader: BufReader<File>) -> Option<ZipContents> {
     if let Ok(mut archive) = ZipArchive::new(reader) {
         let mut ini_files: Vec<String> = vec![];
         let mut py_files: Vec<String> = vec![];
         for i in 0..archive.len() {
-            if let Ok(file) = archive.by_index(i) {
-                let file_name = file.name();
+            if let Ok(mut file) = archive.by_index(i) {
+                let file_name = file.name().to_owned();
                 if file_name.ends_with("ini") {
-                    ini_files.push(file.name().to_owned());
+                    // Inspect the profile straight from the archive, nothing is extracted yet
+                    match gsx_ini_parser::parse_document_reader(&mut file) {
+                        Ok(parsed) if is_gsx_profile(&parsed.document) => {
+                            for diagnostic in parsed.diagnostics.iter() {
+                                debug!("{}: {}", &file_name, diagnostic);
+                            }
+                            ini_files.push(file_name);
+                        }
+                        Ok(_) => warn!("{} is not a GSX Profile, skipping", &file_name),
+                        Err(error) => error!("Error reading {} from Zip Archive: {}", &file_name, error),
+                    }
                 } else if file_name.ends_with("py") {
-                    py_files.push(file.name().to_owned());
+                    py_files.push(file_name);
                 }
             } else {
                 error!("Error reading from Zip Archive");
                 return None;
             }
         }
-        return Some((archive, ini_files, py_files));
+        Some((archive, ini_files, py_files))
     } else {
-        return None;
+        None
     }
 }
 
 fn prepare_ini_import(
     archive: &mut ZipArchive<BufReader<File>>,
-    ini_files: &Vec<String>,
-    extraction_path: &PathBuf,
+    ini_files: &[String],
+    extraction_path: &Path,
 ) -> PathBuf {
     let mut ini_path_to_import = PathBuf::new();
     match ini_files.len() {
         0 => error!("No ini-Files found in Zip Archive"),
         1 => {
-            let file_name = ini_files[0].as_str();
-            ini_path_to_import =
-                extract_file_from_zip(archive.by_name(file_name).unwrap(), &extraction_path);
+            if let Some(path) = extract_or_skip(archive, &ini_files[0], extraction_path) {
+                ini_path_to_import = path;
+            }
         }
         _ => {
             for file in ini_files {
-                extract_file_from_zip(archive.by_name(file).unwrap(), &extraction_path);
+                extract_or_skip(archive, file, extraction_path);
             }
 
             if let Some(path) = rfd::FileDialog::new()
                 .add_filter("GSX-Profile", &["ini"])
-                .set_directory(&extraction_path)
+                .set_directory(extraction_path)
                 .set_title("Multiple Ini-Files found. Choose which one to import")
                 .pick_file()
             {
@@ -99,27 +119,26 @@ fn prepare_ini_import(
 
 fn prepare_py_import(
     archive: &mut ZipArchive<BufReader<File>>,
-    py_files: &Vec<String>,
-    extraction_path: &PathBuf,
-    ini_import_path: &PathBuf,
+    py_files: &[String],
+    extraction_path: &Path,
+    ini_import_path: &Path,
 ) {
     let mut py_to_import_path = PathBuf::new();
     match py_files.len() {
         0 => (),
         1 => {
-            py_to_import_path = extract_file_from_zip(
-                archive.by_name(&py_files[0].as_str()).unwrap(),
-                &extraction_path,
-            );
+            if let Some(path) = extract_or_skip(archive, &py_files[0], extraction_path) {
+                py_to_import_path = path;
+            }
         }
         _ => {
             for file in py_files {
-                extract_file_from_zip(archive.by_name(file).unwrap(), &extraction_path);
+                extract_or_skip(archive, file, extraction_path);
             }
 
             if let Some(path) = rfd::FileDialog::new()
                 .add_filter("GSX-Profile", &["py"])
-                .set_directory(&extraction_path)
+                .set_directory(extraction_path)
                 .set_title("Multiple possible Py-Files found. Choose which one to import or Cancel if none")
                 .pick_file()
             {
@@ -139,46 +158,123 @@ fn prepare_py_import(
     }
 }
 
-fn extract_file_from_zip(mut file: ZipFile, target_path: &PathBuf) -> PathBuf {
-    if let Some(file_name) = file.enclosed_name() {
-        let outpath = target_path.join(file_name.components().next_back().unwrap());
-        debug!("{:?}", outpath);
-
-        if let Some(p) = outpath.parent() {
-            if !p.exists() {
-                fs::create_dir_all(p).unwrap();
-            }
+/// Extracts the entry `file_name`, logging and skipping it if it can't be.
+fn extract_or_skip<R: Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    file_name: &str,
+    target_path: &Path,
+) -> Option<PathBuf> {
+    match extract_file_from_zip(archive, file_name, target_path) {
+        Ok(outpath) => Some(outpath),
+        Err(error) => {
+            error!("Error extracting {} from Zip Archive, skipping: {}", file_name, error);
+            None
         }
+    }
+}
 
-        let mut outfile = fs::File::create(&outpath).unwrap();
-        io::copy(&mut file, &mut outfile).unwrap();
-
-        return outpath;
+/// Extracts the entry `file_name` into `target_path`, without the folders it is in. A partly
+/// written file is removed again.
+fn extract_file_from_zip<R: Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    file_name: &str,
+    target_path: &Path,
+) -> ZipResult<PathBuf> {
+    let mut file = archive.by_name(file_name)?;
+    let Some(name) = file.enclosed_name().and_then(|name| name.file_name().map(PathBuf::from)) else {
+        return Err(ZipError::InvalidArchive("entry is outside of the archive folder".into()));
     };
+    let outpath = target_path.join(name);
+    debug!("{:?}", outpath);
 
-    PathBuf::new()
+    fs::create_dir_all(target_path)?;
+    let mut outfile = fs::File::create(&outpath)?;
+    if let Err(error) = io::copy(&mut file, &mut outfile) {
+        drop(outfile);
+        let _ = fs::remove_file(&outpath);
+        return Err(error.into());
+    }
+    Ok(outpath)
 }
 
-fn import_ini(path: PathBuf) {
+fn import_ini(path: PathBuf, profile_root: &Path) -> Option<PendingMerge> {
     if path.to_str().unwrap().is_empty() {
-        return;
+        return None;
     }
 
-    let to_path = util::get_gsx_profile_path().join(path.file_name().unwrap());
-    match fs::copy(&path, to_path) {
-        Ok(_) => {
-            if let Some(python_file) = get_associated_python_file(&path) {
-                let to_path = util::get_gsx_profile_path().join(python_file.file_name().unwrap());
-                match fs::copy(&path, to_path) {
-                    Ok(_) => {}
-                    Err(error) => {
-                        error!("{:?}", error);
-                    }
-                }
-            }
-        }
-        Err(error) => {
-            error!("{:?}", error)
-        }
+    super::install_profile(&path, profile_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    const PROFILE: &str = "[general]\nversion = 2\n\n[gate A 1]\nmaxwingspan = 36\n";
+
+    /// A new, empty folder for a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gsxman-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A zip archive with an uncompressed profile, so its bytes can be found and broken.
+    fn write_zip(path: &Path) {
+        let mut writer = ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("LSZH/lszh-fsdt.ini", options).unwrap();
+        writer.write_all(PROFILE.as_bytes()).unwrap();
+        writer.finish().unwrap();
+    }
+
+    fn open(path: &Path) -> ZipArchive<BufReader<File>> {
+        ZipArchive::new(BufReader::new(File::open(path).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn extracts_profiles() {
+        let dir = test_dir("zip-extract");
+        let zip_path = dir.join("lszh.zip");
+        write_zip(&zip_path);
+
+        let extraction_path = dir.join("lszh");
+        let ini_files = [String::from("LSZH/lszh-fsdt.ini")];
+        let ini_path = prepare_ini_import(&mut open(&zip_path), &ini_files, &extraction_path);
+        assert_eq!(ini_path, extraction_path.join("lszh-fsdt.ini"));
+        assert_eq!(fs::read_to_string(&ini_path).unwrap(), PROFILE);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn skips_corrupt_entries() {
+        let dir = test_dir("zip-corrupt");
+        let zip_path = dir.join("lszh.zip");
+        write_zip(&zip_path);
+        let mut data = fs::read(&zip_path).unwrap();
+        let start = data
+            .windows(PROFILE.len())
+            .position(|window| window == PROFILE.as_bytes())
+            .unwrap();
+        data[start + 1] = b'G';
+        fs::write(&zip_path, &data).unwrap();
+
+        let extraction_path = dir.join("lszh");
+        let mut archive = open(&zip_path);
+        let ini_files = [String::from("LSZH/lszh-fsdt.ini")];
+        assert_eq!(prepare_ini_import(&mut archive, &ini_files, &extraction_path), PathBuf::new());
+        assert!(!extraction_path.join("lszh-fsdt.ini").exists());
+
+        let missing = [String::from("LSZH/eddf.ini")];
+        assert_eq!(prepare_ini_import(&mut archive, &missing, &extraction_path), PathBuf::new());
+
+        // Cut off before the central directory, the archive can't be opened at all
+        fs::write(&zip_path, &data[..start]).unwrap();
+        assert!(read_files_from_zip_archive(BufReader::new(File::open(&zip_path).unwrap())).is_none());
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,205 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use tracing::{error, warn};
+
+/// Reads the JSON file at `path`. A missing or empty file is an empty object. If the file can't be
+/// read or is corrupt, it is [set aside](set_aside) and the backup [`store`] keeps is loaded
+/// instead.
+pub fn load(path: &Path) -> Value {
+    let error = match read(path) {
+        Ok(data) => return data.unwrap_or_else(|| Value::Object(Default::default())),
+        Err(error) => error,
+    };
+    error!("Could not read {:?}: {}", path, error);
+    set_aside(path);
+
+    let backup_path = backup_path(path);
+    match read(&backup_path) {
+        Ok(Some(data)) => {
+            warn!("Restored {:?} from its backup {:?}", path, backup_path);
+            data
+        }
+        Ok(None) => {
+            error!("There is no backup of {:?}, starting without user data", path);
+            Value::Object(Default::default())
+        }
+        Err(backup_error) => {
+            error!("Could not read the backup {:?} either: {}", backup_path, backup_error);
+            Value::Object(Default::default())
+        }
+    }
+}
+
+/// Replaces the JSON file at `path` like [`replace`]. The old file is kept as backup, if it could
+/// be read.
+pub fn store(path: &Path, data: &Value) -> io::Result<()> {
+    let temp_path = write_temp(path, |file| Ok(serde_json::to_writer_pretty(file, data)?))?;
+
+    // A corrupt file must not replace the last good backup. Files rejected by `load` were already
+    // set aside, so they aren't found here.
+    if let Ok(Some(_)) = read(path) {
+        let backup_path = backup_path(path);
+        fs::copy(path, &backup_path)?;
+        File::open(&backup_path)?.sync_all()?;
+    }
+    fs::rename(&temp_path, path)?;
+    sync_parent(path)
+}
+
+/// Replaces the file at `path` without ever leaving a half written file: `write` writes to a
+/// temporary file next to it, which is synced to disk first and then renamed over the old file.
+pub fn replace(path: &Path, write: impl FnOnce(&mut File) -> io::Result<()>) -> io::Result<()> {
+    let temp_path = write_temp(path, write)?;
+    fs::rename(&temp_path, path)?;
+    sync_parent(path)
+}
+
+/// Writes and syncs the temporary file for `path`. It is removed again if that fails.
+fn write_temp(path: &Path, write: impl FnOnce(&mut File) -> io::Result<()>) -> io::Result<PathBuf> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let write_result = File::create(&temp_path).and_then(|mut temp_file| {
+        write(&mut temp_file)?;
+        temp_file.sync_all()
+    });
+    if let Err(error) = write_result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error);
+    }
+    Ok(temp_path)
+}
+
+/// Moves a file that can't be used out of the way, so it is neither read again nor taken as
+/// backup. It is kept for the user as `.json.unreadable`.
+pub fn set_aside(path: &Path) {
+    let unreadable_path = path.with_extension("json.unreadable");
+    match fs::rename(path, &unreadable_path) {
+        Ok(()) => warn!("Kept the unreadable {:?} as {:?}", path, unreadable_path),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+        Err(error) => error!("Could not move {:?} out of the way: {}", path, error),
+    }
+}
+
+/// Syncs the folder of `path`, so a rename in it survives a crash. Folders can only be opened for
+/// this on Unix, Windows writes renames through on its own.
+fn sync_parent(path: &Path) -> io::Result<()> {
+    if cfg!(unix) {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// `None` if the file doesn't exist or is empty.
+fn read(path: &Path) -> io::Result<Option<Value>> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    if data.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&data)?))
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    /// A new, empty folder for a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gsxman-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn stores_and_loads() {
+        let dir = test_dir("store-and-load");
+        let path = dir.join("userdata.json");
+        assert_eq!(load(&path), json!({}));
+
+        store(&path, &json!({ "version": 1 })).unwrap();
+        assert_eq!(load(&path), json!({ "version": 1 }));
+        assert!(!backup_path(&path).exists());
+
+        store(&path, &json!({ "version": 2 })).unwrap();
+        assert_eq!(load(&path), json!({ "version": 2 }));
+        assert_eq!(read(&backup_path(&path)).unwrap(), Some(json!({ "version": 1 })));
+        assert!(!path.with_extension("json.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn truncated_file_falls_back_to_the_backup() {
+        let dir = test_dir("truncated");
+        let path = dir.join("userdata.json");
+        store(&path, &json!({ "notes": "first" })).unwrap();
+        store(&path, &json!({ "notes": "second" })).unwrap();
+        let data = fs::read_to_string(&path).unwrap();
+        fs::write(&path, &data[..data.len() / 2]).unwrap();
+
+        assert_eq!(load(&path), json!({ "notes": "first" }));
+        assert!(!path.exists());
+        assert!(path.with_extension("json.unreadable").exists());
+
+        // The truncated file must not become the backup
+        store(&path, &json!({ "notes": "third" })).unwrap();
+        assert_eq!(read(&backup_path(&path)).unwrap(), Some(json!({ "notes": "first" })));
+        assert_eq!(load(&path), json!({ "notes": "third" }));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn replaces_files_completely_or_not_at_all() {
+        let dir = test_dir("replace");
+        let path = dir.join("lszh-fsdt.ini");
+        fs::write(&path, "[general]\n").unwrap();
+
+        replace(&path, |file| file.write_all(b"[general]\nversion = 2\n")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[general]\nversion = 2\n");
+
+        let error = replace(&path, |file| {
+            file.write_all(b"[gen")?;
+            Err(io::Error::other("disk full"))
+        });
+        assert!(error.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[general]\nversion = 2\n");
+        assert!(!dir.join("lszh-fsdt.ini.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_file_without_backup_starts_empty() {
+        let dir = test_dir("corrupt");
+        let path = dir.join("userdata.json");
+        fs::write(&path, "{ \"notes\": ").unwrap();
+
+        assert_eq!(load(&path), json!({}));
+        assert_eq!(
+            fs::read_to_string(path.with_extension("json.unreadable")).unwrap(),
+            "{ \"notes\": "
+        );
+
+        store(&path, &json!({ "notes": "new" })).unwrap();
+        assert!(!backup_path(&path).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
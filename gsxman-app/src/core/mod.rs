@@ -2,10 +2,21 @@ use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
 use geoutils::Location;
+use itertools::Itertools;
+use gsx_ini_parser::{DeError, DeiceArea, Diagnostic, MergeResult, PushbackKind, Resolution, StandId};
 use uuid::Uuid;
 use walkers::{extras::Place, Position};
 
+use attributes::{Flag, LoaderType, ParkingSystem, ParkingType, PushbackDirections, WalkerType};
+use conflicts::Conflict;
+use settings::{ProfileIdentity, ProfileRoot, ProfileRootSource};
+use validation::Problem;
+
+pub mod attributes;
+pub mod conflicts;
 pub mod filehandling;
+pub mod settings;
+pub mod validation;
 
 #[derive(Debug, Clone)]
 pub struct Airport {
@@ -14,21 +25,77 @@ pub struct Airport {
     pub location: Location,
 }
 
+/// A position of a GSX profile with the heading it faces, in degrees true.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GsxPosition {
+    pub position: Position,
+    /// `None` for the few keys GSX writes without one, like `parkingsystem_stopposition`.
+    pub heading: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GsxPushbackPath {
+    pub kind: PushbackKind,
+    pub label: Option<String>,
+    pub start: Option<GsxPosition>,
+    pub approach: Vec<GsxPosition>,
+    pub end: GsxPosition,
+}
+
 #[derive(Debug, Clone)]
 pub struct GsxSection {
     pub id: Uuid,
     pub name: String,
-    pub position: Position,
-    pub pushback_label_left: Option<String>,
-    pub pushback_position_left: Option<Position>,
-    pub pushback_label_right: Option<String>,
-    pub pushback_position_right: Option<Position>,
+    pub stand_id: Option<StandId>,
+    /// Where the stand is shown on the map. `None` if the profile has no position for it, then GSX
+    /// uses the parking position of the scenery.
+    pub position: Option<GsxPosition>,
+    pub pushback_paths: Vec<GsxPushbackPath>,
+    pub parking_position: Option<GsxPosition>,
+    pub jetway_position: Option<GsxPosition>,
+    pub stairs_front_position: Option<GsxPosition>,
+    pub stairs_middle_position: Option<GsxPosition>,
+    pub stairs_rear_position: Option<GsxPosition>,
+    pub baggage_loader_front_position: Option<GsxPosition>,
+    pub baggage_loader_main_position: Option<GsxPosition>,
+    pub baggage_loader_rear_position: Option<GsxPosition>,
+    pub baggage_train_front_position: Option<GsxPosition>,
+    pub baggage_train_main_position: Option<GsxPosition>,
+    pub baggage_train_rear_position: Option<GsxPosition>,
+    pub parking_system_stop_position: Option<GsxPosition>,
+    pub parking_system_object_position: Option<GsxPosition>,
+    pub parking_type: Option<ParkingType>,
+    pub pushback_directions: Option<PushbackDirections>,
+    pub loader_types: Vec<LoaderType>,
+    pub walker_type: Option<WalkerType>,
+    pub parking_system: Option<ParkingSystem>,
+    /// Largest wingspan in meters the stand takes.
+    pub max_wingspan: Option<f64>,
+    pub radius_left: Option<f64>,
+    pub radius_right: Option<f64>,
+    pub gate_distance_threshold: Option<f64>,
+    pub airline_codes: Vec<String>,
+    pub has_jetway: Option<Flag>,
+    pub dont_create_jetways: Option<Flag>,
+    pub no_passenger_stairs: Option<Flag>,
+    pub no_passenger_bus: Option<Flag>,
+    pub disable_pax_barriers: Option<Flag>,
+    pub underground_refueling: Option<Flag>,
+    pub ignore_icao_prefixes: Option<Flag>,
+    pub ignore_preferred_exit: Option<Flag>,
+    pub snap_left_pushback: Option<Flag>,
+    pub snap_right_pushback: Option<Flag>,
+    pub user_customized: Option<Flag>,
+    /// Overlaps and crossing pushbacks with other stands of the profile.
+    pub conflicts: Vec<Conflict>,
+    /// Values of the stand that can't be read, with the section and key. They are left out
+    /// instead of dropping the stand, positions instead of being placed at 0,0.
+    pub invalid_values: Vec<DeError>,
 }
 
 #[derive(Debug, Clone)]
 pub struct GsxProfile {
-    pub deice_labels: Vec<String>,
-    pub deice_areas: Vec<Position>,
+    pub deice_areas: Vec<DeiceArea>,
     pub sections: Vec<GsxSection>,
 }
 
@@ -37,25 +104,148 @@ pub struct ProfileFile {
     pub id: Uuid,
     pub file_name: String,
     pub file_location: PathBuf,
+    /// The profile folder the file is in.
+    pub root: ProfileRoot,
     pub airport: Airport,
     pub py_file_location: Option<PathBuf>,
     pub profile_data: Option<GsxProfile>,
     pub has_duplicate_error: bool,
+    pub parse_diagnostics: Vec<Diagnostic>,
+    /// What [`validation::validate_document`] found in the profile.
+    pub problems: Vec<Problem>,
     pub last_modified: DateTime<Utc>,
     pub creator: String,
+    /// Finds the user data of the profile again after it was renamed, moved or re-imported.
+    pub identity: ProfileIdentity,
     pub notes: String,
 }
 
 impl GsxProfile {
     pub fn new() -> Self {
         Self {
-            deice_labels: vec![],
             deice_areas: vec![],
             sections: vec![],
         }
     }
 }
 
+impl GsxSection {
+    /// The area the stand belongs to, e.g. `Gate A`. Sections that don't follow the usual naming
+    /// are grouped as `Other`.
+    pub fn group(&self) -> String {
+        match &self.stand_id {
+            Some(stand_id) => stand_id.group(),
+            None => String::from("Other"),
+        }
+    }
+
+    /// Every service position the profile sets for this stand, with a readable name.
+    pub fn service_positions(&self) -> Vec<(&'static str, GsxPosition)> {
+        [
+            ("Parking", self.parking_position),
+            ("Jetway", self.jetway_position),
+            ("Stairs front", self.stairs_front_position),
+            ("Stairs middle", self.stairs_middle_position),
+            ("Stairs rear", self.stairs_rear_position),
+            ("Baggage loader front", self.baggage_loader_front_position),
+            ("Baggage loader main", self.baggage_loader_main_position),
+            ("Baggage loader rear", self.baggage_loader_rear_position),
+            ("Baggage train front", self.baggage_train_front_position),
+            ("Baggage train main", self.baggage_train_main_position),
+            ("Baggage train rear", self.baggage_train_rear_position),
+            ("Parking system stop", self.parking_system_stop_position),
+            ("Parking system", self.parking_system_object_position),
+        ]
+        .into_iter()
+        .filter_map(|(name, position)| Some((name, position?)))
+        .collect()
+    }
+
+    /// Every attribute the profile sets for this stand as readable name, readable value and the
+    /// value as it is written in the profile.
+    pub fn attributes(&self) -> Vec<(&'static str, String, String)> {
+        let mut attributes = vec![];
+        if let Some(parking_type) = &self.parking_type {
+            attributes.push(("Parking type", parking_type.to_string(), parking_type.to_value()));
+        }
+        if let Some(pushback_directions) = &self.pushback_directions {
+            attributes.push(("Pushback", pushback_directions.to_string(), pushback_directions.to_value()));
+        }
+        if !self.loader_types.is_empty() {
+            attributes.push((
+                "Loaders",
+                self.loader_types.iter().map(LoaderType::to_string).join(", "),
+                self.loader_types.iter().map(LoaderType::to_value).join(" "),
+            ));
+        }
+        if let Some(walker_type) = &self.walker_type {
+            attributes.push(("Passengers", walker_type.to_string(), walker_type.to_value()));
+        }
+        if let Some(parking_system) = &self.parking_system {
+            attributes.push(("Parking system", parking_system.to_string(), parking_system.to_value()));
+        }
+        for (name, meters) in [
+            ("Max wingspan", self.max_wingspan),
+            ("Radius left", self.radius_left),
+            ("Radius right", self.radius_right),
+            ("Gate distance threshold", self.gate_distance_threshold),
+        ] {
+            if let Some(meters) = meters {
+                attributes.push((name, format!("{} m", meters), meters.to_string()));
+            }
+        }
+        if !self.airline_codes.is_empty() {
+            attributes.push((
+                "Airlines",
+                self.airline_codes.join(", "),
+                self.airline_codes.join(" "),
+            ));
+        }
+        for (name, flag) in [
+            ("Has jetway", &self.has_jetway),
+            ("Don't create jetways", &self.dont_create_jetways),
+            ("No passenger stairs", &self.no_passenger_stairs),
+            ("No passenger bus", &self.no_passenger_bus),
+            ("Disable passenger barriers", &self.disable_pax_barriers),
+            ("Underground refueling", &self.underground_refueling),
+            ("Ignore ICAO prefixes", &self.ignore_icao_prefixes),
+            ("Ignore preferred exit", &self.ignore_preferred_exit),
+            ("Snap left pushback", &self.snap_left_pushback),
+            ("Snap right pushback", &self.snap_right_pushback),
+            ("Customized by user", &self.user_customized),
+        ] {
+            if let Some(flag) = flag {
+                attributes.push((name, flag.to_string(), flag.to_value()));
+            }
+        }
+        attributes
+    }
+}
+
+impl GsxPushbackPath {
+    /// The label of the pushback option, or its kind if the profile has no label for it.
+    pub fn label(&self) -> String {
+        match &self.label {
+            Some(label) if !label.is_empty() => label.to_owned(),
+            _ => String::from(match self.kind {
+                PushbackKind::Left => "Left",
+                PushbackKind::Right => "Right",
+                PushbackKind::QuickEdit => "Quick edit",
+                PushbackKind::Custom => "Custom",
+            }),
+        }
+    }
+
+    /// Start, approach points and end, in order.
+    pub fn waypoints(&self) -> Vec<GsxPosition> {
+        self.start
+            .into_iter()
+            .chain(self.approach.iter().copied())
+            .chain([self.end])
+            .collect()
+    }
+}
+
 impl ProfileFile {
     pub fn new(
         file_name: String,
@@ -70,17 +260,32 @@ impl ProfileFile {
             id: Uuid::new_v4(),
             file_name,
             file_location,
+            root: ProfileRoot::new("", PathBuf::new(), ProfileRootSource::Detected),
             airport,
             py_file_location,
             profile_data: None,
             has_duplicate_error: false,
+            parse_diagnostics: vec![],
+            problems: vec![],
             last_modified,
             creator,
+            identity: ProfileIdentity::default(),
             notes
         }
     }
 }
 
+/// An update of an installed profile merged with the local changes, waiting for its conflicts to be
+/// resolved by the user.
+#[derive(Debug, Clone)]
+pub struct PendingMerge {
+    pub installed_file: PathBuf,
+    pub upstream_file: PathBuf,
+    pub result: MergeResult,
+    /// One per conflict, in the same order.
+    pub resolutions: Vec<Resolution>,
+}
+
 pub struct GsxPlace(pub Place);
 
 impl GsxPlace {
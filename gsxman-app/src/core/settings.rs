@@ -0,0 +1,577 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use gsx_ini_parser::IniDocument;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tracing::{debug, info, warn};
+
+use super::ProfileFile;
+
+/// Version of the layout of [`UserData`]. Raise it and add a migration to [`MIGRATIONS`] whenever
+/// a change can't be read from older files with `#[serde(default)]` alone.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Migrations from every older version to the next one. The one at index `n` turns version `n`
+/// into version `n + 1`.
+const MIGRATIONS: [fn(Value) -> Value; SCHEMA_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Everything GSXManager remembers between runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserData {
+    pub version: u64,
+    #[serde(default)]
+    pub settings: Settings,
+    /// What the user keeps for a profile. Entries whose profile is gone are kept as orphans until
+    /// the profile reappears or the user reassigns or deletes them.
+    #[serde(default)]
+    pub profiles: Vec<ProfileUserData>,
+    /// Read from a newer version of GSXManager. Saving it would lose what this version doesn't
+    /// know, so it is never saved.
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+/// Settings of the application. New settings need a `#[serde(default)]` so older files still load.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Folders with GSX profiles the user added to the detected ones.
+    #[serde(default)]
+    pub profile_roots: Vec<ProfileRoot>,
+}
+
+/// A folder GSX profiles are read from, like the one of MSFS 2020 or a shared team folder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileRoot {
+    pub label: String,
+    pub path: PathBuf,
+    /// Only folders the user added are stored, so this is never read from the file.
+    #[serde(skip)]
+    pub source: ProfileRootSource,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProfileRootSource {
+    /// Found in one of the places GSX is usually installed to.
+    Detected,
+    #[default]
+    User,
+    /// Given with `--profile-path` or `GSXMAN_PROFILE_PATH`, replacing all other folders.
+    Override,
+}
+
+impl ProfileRoot {
+    pub fn new(label: &str, path: PathBuf, source: ProfileRootSource) -> Self {
+        Self {
+            label: String::from(label),
+            path,
+            source,
+        }
+    }
+}
+
+impl fmt::Display for ProfileRootSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileRootSource::Detected => write!(f, "Detected"),
+            ProfileRootSource::User => write!(f, "Added"),
+            ProfileRootSource::Override => write!(f, "Override"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileUserData {
+    pub identity: ProfileIdentity,
+    /// Where the profile was last seen. Only a hint, the profile is found by its identity.
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// What identifies a profile independent of its file name and folder.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileIdentity {
+    pub icao: String,
+    #[serde(default)]
+    pub creator: String,
+    /// Hash of the sections and entries of the profile, see [`ProfileIdentity::new`]. Empty if
+    /// it isn't known.
+    #[serde(default)]
+    pub fingerprint: String,
+}
+
+impl ProfileIdentity {
+    /// The fingerprint only covers sections and entries, so comments, whitespace, line endings
+    /// and the encoding of the file don't change it.
+    pub fn new(icao: &str, creator: &str, document: Option<&IniDocument>) -> Self {
+        Self {
+            icao: icao.to_uppercase(),
+            creator: String::from(creator),
+            fingerprint: document.map(fingerprint).unwrap_or_default(),
+        }
+    }
+
+    fn is_same_creator(&self, other: &ProfileIdentity) -> bool {
+        self.icao == other.icao && self.creator == other.creator
+    }
+}
+
+impl ProfileUserData {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
+
+impl Default for UserData {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            settings: Settings::default(),
+            profiles: vec![],
+            read_only: false,
+        }
+    }
+}
+
+impl UserData {
+    /// Reads user data of any version, migrating it to the current one first. Files without a
+    /// version are from before there was one. Newer files are read as far as possible, but are
+    /// [read only](UserData::read_only).
+    pub fn from_value(mut value: Value) -> serde_json::Result<Self> {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > SCHEMA_VERSION {
+            warn!(
+                "User data is version {}, newer than {}. Changes won't be saved",
+                version, SCHEMA_VERSION
+            );
+        }
+        for (from_version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            debug!("Migrating user data from version {}", from_version);
+            value = migration(value);
+        }
+        let mut user_data: UserData = serde_json::from_value(value)?;
+        user_data.read_only = version > SCHEMA_VERSION;
+        user_data.version = user_data.version.max(SCHEMA_VERSION);
+        Ok(user_data)
+    }
+
+    /// Matches the entries with the installed profiles and moves them to the current identity and
+    /// path of their profile. A profile is matched by, in this order, its identity, its path or,
+    /// if only one entry and one profile are left for an airport and creator, by those. Returns
+    /// whether anything changed.
+    pub fn attach_profiles<'a>(&mut self, profiles: impl IntoIterator<Item = &'a ProfileFile>) -> bool {
+        let mut attached = vec![false; self.profiles.len()];
+        let mut changed = false;
+        let mut unmatched: Vec<(&ProfileIdentity, String)> = vec![];
+        for profile in profiles {
+            let path = path_string(&profile.file_location);
+            let index = self.find_entry(&attached, |entry| entry.identity == profile.identity, &path);
+            match index {
+                Some(index) => {
+                    attached[index] = true;
+                    changed |= self.move_entry(index, &profile.identity, &path);
+                }
+                None => unmatched.push((&profile.identity, path)),
+            }
+        }
+
+        let mut still_unmatched = vec![];
+        for (identity, path) in unmatched {
+            let index = self.find_entry(
+                &attached,
+                |entry| entry.path == path && entry.identity.icao == identity.icao,
+                &path,
+            );
+            match index {
+                Some(index) => {
+                    attached[index] = true;
+                    changed |= self.move_entry(index, identity, &path);
+                }
+                None => still_unmatched.push((identity, path)),
+            }
+        }
+
+        for (identity, path) in still_unmatched.iter() {
+            let same_creator = |other: &ProfileIdentity| other.is_same_creator(identity);
+            let entries = (0..self.profiles.len())
+                .filter(|&index| !attached[index] && same_creator(&self.profiles[index].identity))
+                .collect::<Vec<_>>();
+            let profile_count = still_unmatched
+                .iter()
+                .filter(|(other, _)| same_creator(other))
+                .count();
+            if let (&[index], 1) = (entries.as_slice(), profile_count) {
+                attached[index] = true;
+                changed |= self.move_entry(index, identity, path);
+            }
+        }
+        changed
+    }
+
+    /// Indexes of the entries of [`UserData::profiles`] that belong to none of the profiles.
+    pub fn orphaned_profiles<'a>(
+        &self,
+        profiles: impl IntoIterator<Item = &'a ProfileFile> + Clone,
+    ) -> Vec<usize> {
+        (0..self.profiles.len())
+            .filter(|&index| {
+                !profiles
+                    .clone()
+                    .into_iter()
+                    .any(|profile| profile.identity == self.profiles[index].identity)
+            })
+            .collect()
+    }
+
+    pub fn profile(&self, profile: &ProfileFile) -> Option<&ProfileUserData> {
+        let path = path_string(&profile.file_location);
+        self.profiles
+            .iter()
+            .filter(|entry| entry.identity == profile.identity)
+            .max_by_key(|entry| entry.path == path)
+    }
+
+    pub fn profile_mut(&mut self, profile: &ProfileFile) -> &mut ProfileUserData {
+        let path = path_string(&profile.file_location);
+        let index = self.find_entry(&[], |entry| entry.identity == profile.identity, &path);
+        let index = index.unwrap_or_else(|| {
+            self.profiles.push(ProfileUserData {
+                identity: profile.identity.clone(),
+                path,
+                notes: String::new(),
+            });
+            self.profiles.len() - 1
+        });
+        &mut self.profiles[index]
+    }
+
+    /// Gives an orphaned entry to a profile. Notes the profile already has are kept above the
+    /// ones of the orphan.
+    pub fn reassign(&mut self, index: usize, profile: &ProfileFile) {
+        let orphan = self.profiles.remove(index);
+        let entry = self.profile_mut(profile);
+        if entry.notes.is_empty() {
+            entry.notes = orphan.notes;
+        } else if !orphan.notes.is_empty() {
+            entry.notes = format!("{}\n\n{}", entry.notes, orphan.notes);
+        }
+    }
+
+    /// Drops entries with nothing in them.
+    pub fn remove_empty_profiles(&mut self) {
+        self.profiles.retain(|entry| !entry.is_empty());
+    }
+
+    /// The first entry matching `is_match` that isn't attached yet, preferring one at `path`.
+    fn find_entry(
+        &self,
+        attached: &[bool],
+        is_match: impl Fn(&ProfileUserData) -> bool,
+        path: &str,
+    ) -> Option<usize> {
+        (0..self.profiles.len())
+            .filter(|&index| !attached.get(index).copied().unwrap_or_default())
+            .filter(|&index| is_match(&self.profiles[index]))
+            .max_by_key(|&index| (self.profiles[index].path == path, std::cmp::Reverse(index)))
+    }
+
+    fn move_entry(&mut self, index: usize, identity: &ProfileIdentity, path: &str) -> bool {
+        let entry = &mut self.profiles[index];
+        if entry.identity == *identity && entry.path == path {
+            return false;
+        }
+        info!("Notes of {} moved from {} to {}", identity.icao, entry.path, path);
+        entry.identity = identity.clone();
+        entry.path = String::from(path);
+        true
+    }
+}
+
+/// Version 0 was an object of profile file paths, each with the notes of the profile.
+fn migrate_v0_to_v1(value: Value) -> Value {
+    let profiles: Map<String, Value> = match value {
+        Value::Object(profiles) => profiles
+            .into_iter()
+            .filter_map(|(path, profile)| {
+                let notes = match profile.get("notes")? {
+                    Value::String(notes) => notes.clone(),
+                    Value::Null => String::new(),
+                    notes => notes.to_string(),
+                };
+                Some((path, json!({ "notes": notes })))
+            })
+            .collect(),
+        _ => Map::new(),
+    };
+    json!({
+        "version": 1,
+        "profiles": profiles,
+    })
+}
+
+/// Version 1 kept the notes by the path of the profile file. The identity is filled in from the
+/// file name as far as possible and completed once the profile is found at that path.
+fn migrate_v1_to_v2(mut value: Value) -> Value {
+    let profiles: Vec<Value> = match value.get("profiles") {
+        Some(Value::Object(profiles)) => profiles
+            .iter()
+            .map(|(path, profile)| {
+                // Split on both separators, the paths may have been written on another system
+                let icao = path
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .and_then(|file_name| file_name.get(..4))
+                    .unwrap_or_default()
+                    .to_uppercase();
+                json!({
+                    "identity": { "icao": icao },
+                    "path": path,
+                    "notes": profile.get("notes").cloned().unwrap_or_default(),
+                })
+            })
+            .collect(),
+        _ => vec![],
+    };
+    value["version"] = json!(2);
+    value["profiles"] = json!(profiles);
+    value
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// FNV-1a over the section names and entries. Unlike the hasher of the standard library it is
+/// the same in every version of the application.
+fn fingerprint(document: &IniDocument) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    let mut add = |text: &str| {
+        // The separator keeps "ab" + "c" apart from "a" + "bc"
+        for byte in text.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+    for section in document.sections() {
+        add(&section.name().to_lowercase());
+        for (key, value) in section.entries() {
+            add(&key.to_lowercase());
+            add(value.trim());
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use geoutils::Location;
+
+    use crate::core::Airport;
+
+    #[test]
+    fn migrates_unversioned_user_data() {
+        let legacy = json!({
+            "C:\\GSX\\MSFS\\lszh-fsdt.ini": { "notes": "Stand E 27 is too small" },
+            "C:\\GSX\\MSFS\\eddf-abc.ini": { "notes": null },
+        });
+        let user_data = UserData::from_value(legacy).unwrap();
+        assert_eq!(user_data.version, SCHEMA_VERSION);
+        assert!(!user_data.read_only);
+
+        let lszh = user_data
+            .profiles
+            .iter()
+            .find(|profile| profile.path.ends_with("lszh-fsdt.ini"))
+            .unwrap();
+        assert_eq!(lszh.notes, "Stand E 27 is too small");
+        assert_eq!(lszh.identity.icao, "LSZH");
+        assert_eq!(lszh.identity.fingerprint, "");
+        let eddf = user_data
+            .profiles
+            .iter()
+            .find(|profile| profile.path.ends_with("eddf-abc.ini"))
+            .unwrap();
+        assert_eq!(eddf.notes, "");
+    }
+
+    #[test]
+    fn migrates_version_1() {
+        let v1 = json!({
+            "version": 1,
+            "settings": {},
+            "profiles": {
+                "/gsx/MSFS/lszh-fsdt.ini": { "notes": "Line one\nLine two" },
+            },
+        });
+        let user_data = UserData::from_value(v1).unwrap();
+        assert_eq!(user_data.version, 2);
+        assert_eq!(
+            user_data.profiles,
+            vec![ProfileUserData {
+                identity: ProfileIdentity {
+                    icao: String::from("LSZH"),
+                    creator: String::new(),
+                    fingerprint: String::new(),
+                },
+                path: String::from("/gsx/MSFS/lszh-fsdt.ini"),
+                notes: String::from("Line one\nLine two"),
+            }]
+        );
+
+        // Migrated user data reads back the same
+        let saved = serde_json::to_value(&user_data).unwrap();
+        assert_eq!(UserData::from_value(saved).unwrap(), user_data);
+    }
+
+    #[test]
+    fn newer_user_data_is_read_only() {
+        let newer = json!({
+            "version": SCHEMA_VERSION + 1,
+            "profiles": [{ "identity": { "icao": "LSZH" }, "notes": "Kept", "tags": ["hub"] }],
+            "layout": { "map_width": 400 },
+        });
+        let user_data = UserData::from_value(newer).unwrap();
+        assert!(user_data.read_only);
+        assert_eq!(user_data.version, SCHEMA_VERSION + 1);
+        assert_eq!(user_data.profiles[0].notes, "Kept");
+
+        assert!(!UserData::default().read_only);
+    }
+
+    fn profile(path: &str, identity: ProfileIdentity) -> ProfileFile {
+        let airport = Airport {
+            icao: identity.icao.clone(),
+            name: String::new(),
+            location: Location::new(47.46, 8.55),
+        };
+        let file_location = PathBuf::from(path);
+        let file_name = file_location.file_name().unwrap().to_string_lossy().into_owned();
+        let mut profile = ProfileFile::new(
+            file_name,
+            file_location,
+            airport,
+            None,
+            Utc::now(),
+            identity.creator.clone(),
+            String::new(),
+        );
+        profile.identity = identity;
+        profile
+    }
+
+    fn identity(icao: &str, creator: &str, fingerprint: &str) -> ProfileIdentity {
+        ProfileIdentity {
+            icao: String::from(icao),
+            creator: String::from(creator),
+            fingerprint: String::from(fingerprint),
+        }
+    }
+
+    fn user_data(entries: &[(&str, ProfileIdentity, &str)]) -> UserData {
+        UserData {
+            profiles: entries
+                .iter()
+                .map(|(path, identity, notes)| ProfileUserData {
+                    identity: identity.clone(),
+                    path: String::from(*path),
+                    notes: String::from(*notes),
+                })
+                .collect(),
+            ..UserData::default()
+        }
+    }
+
+    #[test]
+    fn attaches_renamed_profiles_by_identity() {
+        let lszh = identity("LSZH", "FSDT", "1111");
+        let mut user_data = user_data(&[("/gsx/MSFS/lszh-fsdt.ini", lszh.clone(), "Notes")]);
+        let profiles = [profile("/gsx/MSFS/lszh-fsdt-v2.ini", lszh)];
+
+        assert!(user_data.attach_profiles(&profiles));
+        assert_eq!(user_data.profiles[0].path, "/gsx/MSFS/lszh-fsdt-v2.ini");
+        assert_eq!(user_data.profile(&profiles[0]).unwrap().notes, "Notes");
+        assert!(!user_data.attach_profiles(&profiles));
+    }
+
+    #[test]
+    fn attaches_profiles_moved_to_another_root() {
+        let lszh = identity("LSZH", "FSDT", "1111");
+        let mut user_data = user_data(&[("/gsx/MSFS/lszh-fsdt.ini", lszh.clone(), "Notes")]);
+        let profiles = [profile("/proton/GSX/MSFS2024/lszh-fsdt.ini", lszh)];
+
+        assert!(user_data.attach_profiles(&profiles));
+        assert_eq!(user_data.profiles[0].path, "/proton/GSX/MSFS2024/lszh-fsdt.ini");
+        assert!(user_data.orphaned_profiles(&profiles).is_empty());
+    }
+
+    #[test]
+    fn attaches_edited_profiles_by_path() {
+        let mut user_data = user_data(&[
+            ("/gsx/MSFS/lszh-fsdt.ini", identity("LSZH", "FSDT", "1111"), "FSDT"),
+            ("/gsx/MSFS/lszh-other.ini", identity("LSZH", "Other", "2222"), "Other"),
+        ]);
+        let edited = identity("LSZH", "FSDT", "3333");
+        let profiles = [
+            profile("/gsx/MSFS/lszh-fsdt.ini", edited.clone()),
+            profile("/gsx/MSFS/lszh-other.ini", identity("LSZH", "Other", "2222")),
+        ];
+
+        assert!(user_data.attach_profiles(&profiles));
+        assert_eq!(user_data.profiles[0].identity, edited);
+        assert_eq!(user_data.profile(&profiles[0]).unwrap().notes, "FSDT");
+        assert_eq!(user_data.profile(&profiles[1]).unwrap().notes, "Other");
+    }
+
+    #[test]
+    fn attaches_edited_and_renamed_profiles_by_creator() {
+        let mut user_data = user_data(&[(
+            "/gsx/MSFS/lszh-fsdt.ini",
+            identity("LSZH", "FSDT", "1111"),
+            "Notes",
+        )]);
+        let profiles = [profile("/gsx/MSFS/lszh-fsdt-v2.ini", identity("LSZH", "FSDT", "3333"))];
+
+        assert!(user_data.attach_profiles(&profiles));
+        assert_eq!(user_data.profile(&profiles[0]).unwrap().notes, "Notes");
+    }
+
+    #[test]
+    fn keeps_ambiguous_profiles_of_the_same_creator_orphaned() {
+        let mut user_data = user_data(&[(
+            "/gsx/MSFS/lszh-fsdt.ini",
+            identity("LSZH", "FSDT", "1111"),
+            "Notes",
+        )]);
+        let profiles = [
+            profile("/gsx/MSFS/lszh-fsdt-a.ini", identity("LSZH", "FSDT", "2222")),
+            profile("/gsx/MSFS/lszh-fsdt-b.ini", identity("LSZH", "FSDT", "3333")),
+        ];
+
+        assert!(!user_data.attach_profiles(&profiles));
+        assert_eq!(user_data.profiles[0].path, "/gsx/MSFS/lszh-fsdt.ini");
+        assert_eq!(user_data.orphaned_profiles(&profiles), vec![0]);
+        assert!(profiles.iter().all(|profile| user_data.profile(profile).is_none()));
+    }
+
+    #[test]
+    fn keeps_profiles_of_the_same_creator_orphaned_with_several_entries() {
+        let mut user_data = user_data(&[
+            ("/gsx/MSFS/lszh-fsdt-a.ini", identity("LSZH", "FSDT", "1111"), "A"),
+            ("/gsx/MSFS/lszh-fsdt-b.ini", identity("LSZH", "FSDT", "2222"), "B"),
+        ]);
+        let profiles = [profile("/gsx/MSFS/lszh-fsdt.ini", identity("LSZH", "FSDT", "3333"))];
+
+        assert!(!user_data.attach_profiles(&profiles));
+        assert_eq!(user_data.orphaned_profiles(&profiles), vec![0, 1]);
+    }
+}
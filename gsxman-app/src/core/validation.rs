@@ -0,0 +1,474 @@
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use geoutils::Location;
+use gsx_ini_parser::{
+    is_position_key, parse_labels, parse_object_position, parse_position,
+    parse_pushback_add_positions, DeError, Diagnostic, DiagnosticKind, IniDocument, Severity,
+    Stand,
+};
+
+use super::Airport;
+
+/// Stands further away from the airport than this are most likely misplaced.
+const MAX_AIRPORT_DISTANCE_METERS: f64 = 10_000.0;
+
+/// Something in a profile that GSX will most likely get wrong, found by [`validate_document`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    /// 1-based line of the key, or of the section header for problems of a whole stand.
+    pub line: usize,
+    pub section: String,
+    pub key: Option<String>,
+    pub kind: ProblemKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProblemKind {
+    /// A position value that can't be read. GSX places it at 0,0 instead.
+    UnreadablePosition(String),
+    /// Any other value that can't be read. The stand is shown without it.
+    UnreadableValue(String),
+    /// A position at 0,0, usually left over from a value that couldn't be read.
+    NullIsland,
+    /// The stand is this many meters away from the airport.
+    FarFromAirport(f64),
+    /// `pushbacklabels` has a label for left and right, so it should have one for every
+    /// direction with an end position.
+    PushbackLabelCount { labels: usize, expected: usize },
+    /// `pushback` allows a direction that has no end position, although the other direction has
+    /// one. Without any, GSX uses its default pushback for every direction.
+    MissingPushbackEnd(&'static str),
+    /// The section appears more than once, GSX reads both as one.
+    DuplicateSection,
+    /// A heading outside of -180 to 360 degrees. GSX writes headings from -180 to 180, older
+    /// profiles from 0 to 360.
+    HeadingOutOfRange(f64),
+}
+
+impl ProblemKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            ProblemKind::UnreadablePosition(_)
+            | ProblemKind::UnreadableValue(_)
+            | ProblemKind::NullIsland => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for ProblemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemKind::UnreadablePosition(error) => {
+                write!(f, "position can't be read ({}), GSX uses 0,0", error)
+            }
+            ProblemKind::UnreadableValue(error) => write!(f, "value can't be read ({})", error),
+            ProblemKind::NullIsland => write!(f, "position is at 0,0"),
+            ProblemKind::FarFromAirport(meters) => {
+                write!(f, "stand is {:.1} km away from the airport", meters / 1000.0)
+            }
+            ProblemKind::PushbackLabelCount { labels, expected } => write!(
+                f,
+                "pushbacklabels has {} label(s), the pushback positions need {}",
+                labels, expected
+            ),
+            ProblemKind::MissingPushbackEnd(direction) => {
+                write!(f, "pushback to the {} has no end position", direction)
+            }
+            ProblemKind::DuplicateSection => write!(f, "section appears more than once"),
+            ProblemKind::HeadingOutOfRange(heading) => {
+                write!(f, "heading {} is outside of -180 to 360", heading)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "{} [{}] {}: {}", self.line, self.section, key, self.kind),
+            None => write!(f, "{} [{}]: {}", self.line, self.section, self.kind),
+        }
+    }
+}
+
+/// Runs every rule over a profile and returns what was found in file order.
+pub fn validate_document(
+    document: &IniDocument,
+    diagnostics: &[Diagnostic],
+    airport: &Airport,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    for diagnostic in diagnostics {
+        if let DiagnosticKind::DuplicateSection(section_name) = &diagnostic.kind {
+            problems.push(problem(diagnostic.line, section_name, None, ProblemKind::DuplicateSection));
+        }
+    }
+
+    // Line of the first header of every stand, of every key and the farthest position from the
+    // airport. Keys in duplicate sections are at the line of the one GSX reads.
+    let mut header_lines: HashMap<&str, usize> = HashMap::new();
+    let mut key_lines: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut farthest: HashMap<&str, (usize, &str, f64)> = HashMap::new();
+    let mut line_number = document.preamble().len();
+    for section in document.sections() {
+        let section_name = section.name();
+        header_lines.entry(section_name).or_insert(line_number + 1);
+        for line in section.lines() {
+            line_number += 1;
+            if section_name.eq_ignore_ascii_case("general") {
+                continue;
+            }
+            let (Some(key), Some(value)) = (line.key(), line.value()) else {
+                continue;
+            };
+            key_lines.insert((section_name, key), line_number);
+            for (latitude, longitude, heading) in positions_of(key, value, |error| {
+                problems.push(problem(
+                    line_number,
+                    section_name,
+                    Some(key),
+                    ProblemKind::UnreadablePosition(error),
+                ))
+            }) {
+                if latitude == 0.0 && longitude == 0.0 {
+                    problems.push(problem(line_number, section_name, Some(key), ProblemKind::NullIsland));
+                    continue;
+                }
+                if let Some(heading) = heading.filter(|heading| !(-180.0..=360.0).contains(heading)) {
+                    problems.push(problem(
+                        line_number,
+                        section_name,
+                        Some(key),
+                        ProblemKind::HeadingOutOfRange(heading),
+                    ));
+                }
+                let distance = airport
+                    .location
+                    .haversine_distance_to(&Location::new(latitude, longitude))
+                    .meters();
+                if farthest
+                    .get(section_name)
+                    .is_none_or(|(_, _, farthest_distance)| distance > *farthest_distance)
+                {
+                    farthest.insert(section_name, (line_number, key, distance));
+                }
+            }
+        }
+    }
+
+    for section_name in document.section_names() {
+        if section_name.eq_ignore_ascii_case("general") {
+            continue;
+        }
+        let header_line = header_lines[section_name];
+        let key_line = |key: &str| key_lines.get(&(section_name, key)).copied().unwrap_or(header_line);
+
+        // Positions were checked above, with the reason GSX can't read them
+        match read_stand(document, section_name) {
+            Ok((_, invalid_values)) => {
+                for error in invalid_values {
+                    let Some(key) = error.key.as_deref().filter(|key| !is_position_key(key)) else {
+                        continue;
+                    };
+                    problems.push(problem(
+                        key_line(key),
+                        section_name,
+                        Some(key),
+                        ProblemKind::UnreadableValue(error.message.clone()),
+                    ));
+                }
+            }
+            Err(error) => problems.push(problem(
+                header_line,
+                section_name,
+                None,
+                ProblemKind::UnreadableValue(error.message),
+            )),
+        }
+
+        if let Some(&(line, key, distance)) = farthest.get(section_name) {
+            if distance > MAX_AIRPORT_DISTANCE_METERS {
+                problems.push(problem(line, section_name, Some(key), ProblemKind::FarFromAirport(distance)));
+            }
+        }
+
+        let has_left_end = document.get(section_name, "pushbackleftpos").is_some();
+        let has_right_end = document.get(section_name, "pushbackrightpos").is_some();
+        let labels = parse_labels(document.get(section_name, "pushbacklabels").unwrap_or_default());
+        let needed = if has_right_end {
+            2
+        } else {
+            usize::from(has_left_end)
+        };
+        if !labels.is_empty() && (labels.len() < needed || labels.len() > 2) {
+            problems.push(problem(
+                key_line("pushbacklabels"),
+                section_name,
+                Some("pushbacklabels"),
+                ProblemKind::PushbackLabelCount {
+                    labels: labels.len(),
+                    expected: if labels.len() > 2 { 2 } else { needed },
+                },
+            ));
+        }
+
+        // GSX writes labels for its default pushbacks as well, so only ends make them custom
+        let has_custom_pushback = has_left_end || has_right_end;
+        let pushback = document.get(section_name, "pushback").map(str::trim);
+        let (needs_left_end, needs_right_end) = match pushback {
+            _ if !has_custom_pushback => (false, false),
+            Some("1") => (true, false),
+            Some("2") => (false, true),
+            Some("3") => (true, true),
+            _ => (false, false),
+        };
+        for (needs_end, has_end, direction) in [
+            (needs_left_end, has_left_end, "left"),
+            (needs_right_end, has_right_end, "right"),
+        ] {
+            if needs_end && !has_end {
+                problems.push(problem(
+                    key_line("pushback"),
+                    section_name,
+                    Some("pushback"),
+                    ProblemKind::MissingPushbackEnd(direction),
+                ));
+            }
+        }
+    }
+
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
+
+/// Reads a stand. Values that can't be read are left out and returned with the stand, so it is
+/// listed with invalid values instead of being dropped. Only errors without a key fail.
+pub fn read_stand(document: &IniDocument, section_name: &str) -> Result<(Stand, Vec<DeError>), DeError> {
+    let mut document = Cow::Borrowed(document);
+    let mut invalid_values = vec![];
+    loop {
+        let error = match gsx_ini_parser::from_section::<Stand>(&document, section_name) {
+            Ok(stand) => {
+                // The name isn't a value of the section, so it isn't deserialized
+                let stand = Stand {
+                    name: String::from(section_name),
+                    ..stand
+                };
+                return Ok((stand, invalid_values));
+            }
+            Err(error) => error,
+        };
+        let Some(key) = error.key.clone() else {
+            return Err(error);
+        };
+        if document.to_mut().remove(section_name, &key).is_none() {
+            return Err(error);
+        }
+        invalid_values.push(error);
+    }
+}
+
+fn problem(line: usize, section: &str, key: Option<&str>, kind: ProblemKind) -> Problem {
+    Problem {
+        severity: kind.severity(),
+        line,
+        section: String::from(section),
+        key: key.map(String::from),
+        kind,
+    }
+}
+
+/// Latitude, longitude and heading of every position in a value. Keys that aren't positions have
+/// none, values that can't be read are passed to `on_error`.
+fn positions_of(
+    key: &str,
+    value: &str,
+    on_error: impl FnOnce(String),
+) -> Vec<(f64, f64, Option<f64>)> {
+    if key == "pushbackaddpos" {
+        return match parse_pushback_add_positions(value) {
+            Ok(add_positions) => add_positions
+                .iter()
+                .flat_map(|add_position| add_position.approach.iter().chain([&add_position.position]))
+                .map(|position| (position.latitude, position.longitude, position.heading))
+                .collect(),
+            Err(error) => {
+                on_error(error.to_string());
+                vec![]
+            }
+        };
+    }
+    if !is_position_key(key) {
+        return vec![];
+    }
+    match parse_position(value) {
+        Ok(position) => vec![(position.latitude, position.longitude, position.heading)],
+        Err(error) => match parse_object_position(value) {
+            Ok(position) => vec![(position.latitude, position.longitude, Some(position.heading))],
+            Err(_) => {
+                on_error(error.to_string());
+                vec![]
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gsx_ini_parser::{parse_document, parse_document_str};
+
+    const STAND: &str = "[gate a 1]\npushback_pos = 47.4535 8.5586 -173.9\n";
+
+    fn lszh() -> Airport {
+        Airport {
+            icao: String::from("LSZH"),
+            name: String::from("Zurich"),
+            location: Location::new(47.4647, 8.5492),
+        }
+    }
+
+    fn validate(text: &str) -> Vec<Problem> {
+        let parsed = parse_document_str(text);
+        validate_document(&parsed.document, &parsed.diagnostics, &lszh())
+    }
+
+    fn kinds(text: &str) -> Vec<ProblemKind> {
+        validate(text).into_iter().map(|problem| problem.kind).collect()
+    }
+
+    #[test]
+    fn valid_stand_has_no_problems() {
+        assert_eq!(kinds(STAND), vec![]);
+    }
+
+    #[test]
+    fn finds_unreadable_positions() {
+        let problems = validate("[gate a 1]\npushback_pos = 47.4535 north\n");
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(problems[0].kind, ProblemKind::UnreadablePosition(_)));
+        assert_eq!((problems[0].line, problems[0].key.as_deref()), (2, Some("pushback_pos")));
+        assert_eq!(problems[0].severity, Severity::Error);
+
+        // Flags aren't positions
+        assert_eq!(kinds("[gate a 1]\nsnapleftpushbackpos = 1\n"), vec![]);
+    }
+
+    #[test]
+    fn finds_unreadable_values() {
+        let text = "[gate a 1]\npushback_pos = 47.4535 8.5586 0\nmaxwingspan = wide\n";
+        let problems = validate(text);
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(problems[0].kind, ProblemKind::UnreadableValue(_)));
+        assert_eq!((problems[0].line, problems[0].key.as_deref()), (3, Some("maxwingspan")));
+
+        assert_eq!(kinds(&text.replace("wide", "36.0")), vec![]);
+    }
+
+    #[test]
+    fn keeps_stands_with_unreadable_values() {
+        let text = "[gate a 1]\npushback_pos = 47.4535 north\nmaxwingspan = wide\nradiusleft = 18.0\n";
+        let document = parse_document_str(text).document;
+        let (stand, invalid_values) = read_stand(&document, "gate a 1").unwrap();
+        assert_eq!(stand.name, "gate a 1");
+        assert_eq!(stand.pushback_pos, None);
+        assert_eq!(stand.max_wingspan, None);
+        assert_eq!(stand.radius_left, Some(18.0));
+        let keys: Vec<_> = invalid_values.iter().map(|error| error.key.as_deref()).collect();
+        assert_eq!(keys, vec![Some("pushback_pos"), Some("maxwingspan")]);
+        // Only the copy is changed
+        assert_eq!(document.get("gate a 1", "maxwingspan"), Some("wide"));
+    }
+
+    #[test]
+    fn finds_positions_at_null_island() {
+        assert_eq!(kinds("[gate a 1]\npushback_pos = 0 0 0\n"), vec![ProblemKind::NullIsland]);
+        // Only exactly 0,0, anything else is just far away
+        let kinds = kinds("[gate a 1]\npushback_pos = 0.001 0 0\n");
+        assert_eq!(kinds.len(), 1);
+        assert!(matches!(kinds[0], ProblemKind::FarFromAirport(_)));
+    }
+
+    #[test]
+    fn finds_stands_far_from_the_airport() {
+        let problems = validate("[gate a 1]\npushback_pos = 47.4535 8.5586 0\njetway_pos = 47.6 8.5586 0\n");
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(problems[0].kind, ProblemKind::FarFromAirport(meters) if meters > 10_000.0));
+        assert_eq!(problems[0].line, 3);
+
+        assert_eq!(kinds("[gate a 1]\npushback_pos = 47.5 8.5586 0\n"), vec![]);
+    }
+
+    #[test]
+    fn checks_the_number_of_pushback_labels() {
+        let text = "[gate a 1]\npushback = 3\npushbacklabels = Left\npushbackleftpos = 47.4535 8.5586 0\npushbackrightpos = 47.4536 8.5586 0\n";
+        let problems = validate(text);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].kind,
+            ProblemKind::PushbackLabelCount {
+                labels: 1,
+                expected: 2
+            }
+        );
+        assert_eq!((problems[0].line, problems[0].key.as_deref()), (3, Some("pushbacklabels")));
+
+        assert_eq!(kinds(&text.replace("= Left", "= Left|Right")), vec![]);
+    }
+
+    #[test]
+    fn finds_pushbacks_without_an_end() {
+        let text = "[gate a 1]\npushbacklabels = Left|Right\npushback = 3\npushbackleftpos = 47.4535 8.5586 0\n";
+        let problems = validate(text);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].kind, ProblemKind::MissingPushbackEnd("right"));
+        assert_eq!((problems[0].line, problems[0].key.as_deref()), (3, Some("pushback")));
+
+        assert_eq!(kinds(&text.replace("pushback = 3", "pushback = 1")), vec![]);
+
+        assert_eq!(
+            kinds("[gate a 1]\npushback = 1\npushbackrightpos = 47.4535 8.5586 0\n"),
+            vec![ProblemKind::MissingPushbackEnd("left")]
+        );
+        // Without custom ends, GSX uses its default pushback
+        assert_eq!(kinds("[gate a 1]\npushback = 3\n"), vec![]);
+        assert_eq!(kinds("[gate a 1]\npushbacklabels = Left|Right\npushback = 3\n"), vec![]);
+    }
+
+    #[test]
+    fn finds_duplicate_sections() {
+        let problems = validate(&format!("{}{}", STAND, STAND));
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].kind, ProblemKind::DuplicateSection);
+        assert_eq!(problems[0].line, 3);
+
+        assert_eq!(kinds(&format!("{}{}", STAND, STAND.replace("a 1", "a 2"))), vec![]);
+    }
+
+    #[test]
+    fn finds_headings_out_of_range() {
+        assert_eq!(
+            kinds("[gate a 1]\npushback_pos = 47.4535 8.5586 400\n"),
+            vec![ProblemKind::HeadingOutOfRange(400.0)]
+        );
+        assert_eq!(
+            kinds("[gate a 1]\npushback_pos = 47.4535 8.5586 -200\n"),
+            vec![ProblemKind::HeadingOutOfRange(-200.0)]
+        );
+
+        // GSX writes headings from -180 to 180
+        assert_eq!(kinds("[gate a 1]\npushback_pos = 47.4535 8.5586 -180\n"), vec![]);
+        assert_eq!(kinds("[gate a 1]\npushback_pos = 47.4535 8.5586 359.9\n"), vec![]);
+    }
+
+    #[test]
+    fn healthy_profile_has_no_problems() {
+        let mut test_file_path = String::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push_str("/../gsx-ini-parser/res/test/lszh-fsdt.ini");
+        let parsed = parse_document(test_file_path).unwrap();
+        let problems = validate_document(&parsed.document, &parsed.diagnostics, &lszh());
+        assert_eq!(problems, vec![]);
+    }
+}
@@ -1,14 +1,140 @@
 extern crate directories;
-use std::path::{Path, PathBuf};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
-use directories::BaseDirs;
+use directories::{BaseDirs, ProjectDirs};
 
-const GSX_PATH: &str = r"virtuali\GSX\MSFS";
-pub fn get_gsx_profile_path() -> PathBuf {
-    let path = get_appdata_path() + r"\" + GSX_PATH;
-    Path::new(path.as_str()).to_owned()
+use crate::core::settings::{ProfileRoot, ProfileRootSource};
+
+/// A file with this name next to the executable, or the `--portable` argument, keeps the user data
+/// next to the executable instead of in the config directory.
+const PORTABLE_MARKER: &str = "portable";
+
+/// Folders of the GSX profiles of each simulator, below `virtuali\GSX` in the roaming AppData.
+const GSX_FOLDERS: [(&str, &str); 2] = [("MSFS 2020", "MSFS"), ("MSFS 2024", "MSFS2024")];
+
+/// Steam app ids of MSFS 2020 and 2024, whose Proton prefixes GSX is installed into on Linux, with
+/// the suffix for the labels of the profile folders in them.
+const STEAM_APP_IDS: [(&str, &str); 2] = [
+    ("1250410", " (Proton, MSFS 2020)"),
+    ("2537590", " (Proton, MSFS 2024)"),
+];
+
+/// Profile folders that replace all others, separated like `PATH`.
+const PROFILE_PATH_VARIABLE: &str = "GSXMAN_PROFILE_PATH";
+/// Like [`PROFILE_PATH_VARIABLE`], can be given more than once.
+const PROFILE_PATH_ARGUMENT: &str = "--profile-path";
+
+/// The GSX profile folders that exist on this machine. If there are none on Windows, the folder GSX
+/// uses for MSFS 2020, so there is a place to import profiles to. Elsewhere there is no such
+/// folder and the user has to add one.
+pub fn get_default_gsx_profile_roots() -> Vec<ProfileRoot> {
+    let mut roots = vec![];
+    for (suffix, appdata_path) in get_appdata_paths() {
+        for (label, folder) in GSX_FOLDERS {
+            let path = appdata_path.join("virtuali").join("GSX").join(folder);
+            if path.is_dir() {
+                roots.push(ProfileRoot::new(&(String::from(label) + suffix), path, ProfileRootSource::Detected));
+            }
+        }
+    }
+    if roots.is_empty() && cfg!(windows) {
+        if let Some(base_dirs) = BaseDirs::new() {
+            let (label, folder) = GSX_FOLDERS[0];
+            let path = base_dirs.preference_dir().join("virtuali").join("GSX").join(folder);
+            roots.push(ProfileRoot::new(label, path, ProfileRootSource::Detected));
+        }
+    }
+    roots
+}
+
+/// The profile folders given on the command line, or else in the environment.
+pub fn get_gsx_profile_roots_override() -> Option<Vec<ProfileRoot>> {
+    let arguments: Vec<String> = env::args().collect();
+    let mut paths: Vec<PathBuf> = arguments
+        .windows(2)
+        .filter(|arguments| arguments[0] == PROFILE_PATH_ARGUMENT)
+        .map(|arguments| PathBuf::from(&arguments[1]))
+        .collect();
+    if paths.is_empty() {
+        if let Some(variable) = env::var_os(PROFILE_PATH_VARIABLE) {
+            paths = env::split_paths(&variable)
+                .filter(|path| !path.as_os_str().is_empty())
+                .collect();
+        }
+    }
+    if paths.is_empty() {
+        return None;
+    }
+    let roots = paths
+        .into_iter()
+        .map(|path| ProfileRoot::new(&get_folder_name(&path), path, ProfileRootSource::Override))
+        .collect();
+    Some(roots)
+}
+
+/// Label for a folder without one.
+pub fn get_folder_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(folder_name) => folder_name.to_string_lossy().into_owned(),
+        None => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// The roaming AppData folders GSX may be installed in, with a suffix for the label of its
+/// profile folders. Outside of Windows these are in Wine and Proton prefixes.
+fn get_appdata_paths() -> Vec<(&'static str, PathBuf)> {
+    let Some(base_dirs) = BaseDirs::new() else {
+        return vec![];
+    };
+    if cfg!(windows) {
+        return vec![("", base_dirs.preference_dir().to_owned())];
+    }
+
+    let mut appdata_paths = vec![];
+    let wine_prefix = env::var_os("WINEPREFIX")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| base_dirs.home_dir().join(".wine"));
+    if let Ok(user) = env::var("USER") {
+        appdata_paths.push((
+            " (Wine)",
+            wine_prefix.join("drive_c").join("users").join(user).join("AppData").join("Roaming"),
+        ));
+    }
+    let steam_path = base_dirs.data_local_dir().join("Steam").join("steamapps").join("compatdata");
+    for (app_id, suffix) in STEAM_APP_IDS {
+        appdata_paths.push((
+            suffix,
+            steam_path
+                .join(app_id)
+                .join("pfx")
+                .join("drive_c")
+                .join("users")
+                .join("steamuser")
+                .join("AppData")
+                .join("Roaming"),
+        ));
+    }
+    appdata_paths
+}
+
+/// Where GSXManager keeps its own files, like the user data.
+pub fn get_config_path() -> PathBuf {
+    if let Some(portable_path) = get_portable_path() {
+        return portable_path;
+    }
+    match ProjectDirs::from("", "", "GSXManager") {
+        Some(project_dirs) => project_dirs.config_dir().to_owned(),
+        None => PathBuf::from("."),
+    }
 }
 
-fn get_appdata_path() -> String {
-    String::from(BaseDirs::new().unwrap().preference_dir().to_str().unwrap())
+fn get_portable_path() -> Option<PathBuf> {
+    let executable_path = env::current_exe().ok()?;
+    let executable_dir = executable_path.parent()?;
+    let is_portable = env::args().any(|argument| argument == "--portable")
+        || executable_dir.join(PORTABLE_MARKER).exists();
+    is_portable.then(|| executable_dir.to_owned())
 }